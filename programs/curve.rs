@@ -0,0 +1,201 @@
+// programs/meme_launcher/src/curve.rs
+
+// Bonding-curve math with no anchor or Solana types, so anything that can build plain Rust
+// prices trades exactly as the program does. Supplies and amounts are in token base units and
+// costs in lamports. Every pricing function returns None on overflow or an unpriceable range,
+// which the program reports as InvalidPriceCalculation. The `wasm` feature adds wasm-bindgen
+// exports for the web client; the crate's manifest declares it alongside wasm-bindgen.
+
+pub const MAX_BPS: u16 = 10_000;
+// curve_ratio is fixed point with 9 decimal places, so fractional slopes are expressible
+pub const CURVE_RATIO_SCALE: u64 = 1_000_000_000;
+
+// Direction every division in curve and fee math rounds. Lamports flowing into the vault
+// (buy costs, fees charged) round up and lamports flowing out (sell proceeds, fee shares,
+// refunds) round down, so accumulated rounding dust can only stay in the vault.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+impl Rounding {
+    pub fn div(self, numerator: u128, denominator: u128) -> u128 {
+        match self {
+            Rounding::Down => numerator / denominator,
+            Rounding::Up => numerator.div_ceil(denominator),
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            Rounding::Down => Rounding::Up,
+            Rounding::Up => Rounding::Down,
+        }
+    }
+}
+
+// Helper function to calculate price based on bonding curve
+pub fn calculate_price(
+    current_supply: u64,
+    amount: u64,
+    curve_ratio: u64,
+    decimals: u8,
+    rounding: Rounding,
+) -> Option<u64> {
+    // Linear bonding curve over whole tokens: the price at supply s is s * curve_ratio, so the
+    // range costs the area under it, curve_ratio * (end^2 - start^2) / 2, with supplies
+    // divided by 10^decimals and the ratio by CURVE_RATIO_SCALE. The cost depends only on the
+    // range, not on how it is split into trades. end^2 - start^2 is factored as
+    // amount * (end + start), and one token unit is divided out before applying the ratio to
+    // keep the product within u128. Both divisions round the same way, so the result is never
+    // on the wrong side of exact.
+    let token_unit = 10u128.pow(decimals as u32);
+    let span = (amount as u128).checked_mul(2 * current_supply as u128 + amount as u128)?;
+    let price = rounding.div(
+        rounding.div(span, token_unit).checked_mul(curve_ratio as u128)?,
+        2 * token_unit * CURVE_RATIO_SCALE as u128,
+    );
+    price.try_into().ok()
+}
+
+// Curve ratio in force at `supply`: the base ratio times the multiplier of every stage whose
+// threshold it has reached. Stages are (supply_threshold, multiplier_bps) in threshold order.
+pub fn ratio_at(
+    curve_ratio: u64,
+    stages: impl Iterator<Item = (u64, u16)>,
+    supply: u64,
+) -> Option<u64> {
+    let mut ratio = curve_ratio as u128;
+    for (supply_threshold, multiplier_bps) in stages {
+        if supply < supply_threshold {
+            break;
+        }
+        ratio = ratio.checked_mul(multiplier_bps as u128)? / MAX_BPS as u128;
+    }
+    ratio.try_into().ok()
+}
+
+// Price `amount` tokens starting at `start_supply`, splitting the range at stage thresholds
+pub fn staged_cost(
+    curve_ratio: u64,
+    stages: impl Iterator<Item = (u64, u16)> + Clone,
+    decimals: u8,
+    start_supply: u64,
+    amount: u64,
+    rounding: Rounding,
+) -> Option<u64> {
+    let mut supply = start_supply;
+    let mut remaining = amount;
+    let mut cost: u64 = 0;
+
+    for (supply_threshold, _) in stages.clone() {
+        if remaining == 0 {
+            break;
+        }
+        if supply >= supply_threshold {
+            continue;
+        }
+
+        let segment = remaining.min(supply_threshold - supply);
+        let ratio = ratio_at(curve_ratio, stages.clone(), supply)?;
+        cost = cost.checked_add(calculate_price(supply, segment, ratio, decimals, rounding)?)?;
+        supply += segment;
+        remaining -= segment;
+    }
+
+    if remaining > 0 {
+        let ratio = ratio_at(curve_ratio, stages, supply)?;
+        cost = cost.checked_add(calculate_price(supply, remaining, ratio, decimals, rounding)?)?;
+    }
+    Some(cost)
+}
+
+// Constant-product cost between two supplies. The virtual SOL side at a supply is
+// k / (virtual tokens - tokens sold past `initial_supply`), so buys and sells over the same
+// range match.
+pub fn virtual_reserves_cost(
+    virtual_sol_reserves: u64,
+    virtual_token_reserves: u64,
+    initial_supply: u64,
+    start_supply: u64,
+    amount: u64,
+    rounding: Rounding,
+) -> Option<u64> {
+    let k = virtual_sol_reserves as u128 * virtual_token_reserves as u128;
+    let sol_at = |supply: u64, rounding: Rounding| {
+        let sold = supply.saturating_sub(initial_supply) as u128;
+        let tokens_left = (virtual_token_reserves as u128)
+            .checked_sub(sold)
+            .filter(|left| *left > 0)?;
+        Some(rounding.div(k, tokens_left))
+    };
+
+    // Widen the range for buys and narrow it for sells
+    let end_supply = start_supply.checked_add(amount)?;
+    let cost =
+        sol_at(end_supply, rounding)?.saturating_sub(sol_at(start_supply, rounding.reverse())?);
+    cost.try_into().ok()
+}
+
+// Convert a whole-number slope plus fractional nanos into a fixed-point curve_ratio
+pub fn curve_ratio_from_parts(whole: u64, nanos: u64) -> Option<u64> {
+    if nanos >= CURVE_RATIO_SCALE {
+        return None;
+    }
+    whole.checked_mul(CURVE_RATIO_SCALE)?.checked_add(nanos)
+}
+
+// Split a fixed-point curve_ratio back into its whole and fractional (nanos) parts
+pub fn curve_ratio_to_parts(curve_ratio: u64) -> (u64, u64) {
+    (curve_ratio / CURVE_RATIO_SCALE, curve_ratio % CURVE_RATIO_SCALE)
+}
+
+// Quotes for the web client. Buys round up and sells round down, as on chain; stages are
+// passed as parallel threshold and multiplier arrays.
+#[cfg(feature = "wasm")]
+mod wasm {
+    use super::*;
+    use wasm_bindgen::prelude::wasm_bindgen;
+
+    fn rounding(is_buy: bool) -> Rounding {
+        if is_buy {
+            Rounding::Up
+        } else {
+            Rounding::Down
+        }
+    }
+
+    #[wasm_bindgen(js_name = stagedCost)]
+    pub fn staged_cost_js(
+        curve_ratio: u64,
+        stage_thresholds: Vec<u64>,
+        stage_multipliers_bps: Vec<u16>,
+        decimals: u8,
+        start_supply: u64,
+        amount: u64,
+        is_buy: bool,
+    ) -> Option<u64> {
+        let stages = stage_thresholds.into_iter().zip(stage_multipliers_bps);
+        staged_cost(curve_ratio, stages, decimals, start_supply, amount, rounding(is_buy))
+    }
+
+    #[wasm_bindgen(js_name = virtualReservesCost)]
+    pub fn virtual_reserves_cost_js(
+        virtual_sol_reserves: u64,
+        virtual_token_reserves: u64,
+        initial_supply: u64,
+        start_supply: u64,
+        amount: u64,
+        is_buy: bool,
+    ) -> Option<u64> {
+        virtual_reserves_cost(
+            virtual_sol_reserves,
+            virtual_token_reserves,
+            initial_supply,
+            start_supply,
+            amount,
+            rounding(is_buy),
+        )
+    }
+}
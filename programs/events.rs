@@ -0,0 +1,138 @@
+// programs/meme_launcher/src/events.rs
+
+// Program events. An event's discriminator is the first 8 bytes of
+// sha256("event:<StructName>"); the constants below export them so indexers can match raw
+// log data without loading the IDL. A shipped event's layout never changes: a schema change
+// adds a new struct with a versioned name (e.g. `TradeEventV2`) and its own discriminator,
+// so consumers of the old one keep decoding it.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+// Bumped whenever an event is added or superseded
+pub const SCHEMA_VERSION: u8 = 4;
+
+pub const TRADE_EVENT_DISCRIMINATOR: [u8; 8] = TradeEvent::DISCRIMINATOR;
+pub const DONATION_EVENT_DISCRIMINATOR: [u8; 8] = DonationEvent::DISCRIMINATOR;
+pub const RAFFLE_DRAWN_EVENT_DISCRIMINATOR: [u8; 8] = RaffleDrawnEvent::DISCRIMINATOR;
+pub const WITHDRAWAL_REQUESTED_EVENT_DISCRIMINATOR: [u8; 8] =
+    WithdrawalRequestedEvent::DISCRIMINATOR;
+pub const WITHDRAWAL_CLAIMED_EVENT_DISCRIMINATOR: [u8; 8] = WithdrawalClaimedEvent::DISCRIMINATOR;
+pub const TOKENOMICS_COMMITTED_EVENT_DISCRIMINATOR: [u8; 8] =
+    TokenomicsCommittedEvent::DISCRIMINATOR;
+pub const PROTOCOL_FEES_CLAIMED_EVENT_DISCRIMINATOR: [u8; 8] =
+    ProtocolFeesClaimedEvent::DISCRIMINATOR;
+pub const LAUNCH_REPORTED_EVENT_DISCRIMINATOR: [u8; 8] = LaunchReportedEvent::DISCRIMINATOR;
+pub const CURVE_RETUNED_EVENT_DISCRIMINATOR: [u8; 8] = CurveRetunedEvent::DISCRIMINATOR;
+pub const FAIR_MINT_FINALIZED_EVENT_DISCRIMINATOR: [u8; 8] = FairMintFinalizedEvent::DISCRIMINATOR;
+pub const LAUNCH_CANCELLED_EVENT_DISCRIMINATOR: [u8; 8] = LaunchCancelledEvent::DISCRIMINATOR;
+pub const BUYBACK_BURNED_EVENT_DISCRIMINATOR: [u8; 8] = BuybackBurnedEvent::DISCRIMINATOR;
+pub const LAUNCH_VAULT_TOPPED_UP_EVENT_DISCRIMINATOR: [u8; 8] =
+    LaunchVaultToppedUpEvent::DISCRIMINATOR;
+
+#[event]
+pub struct TradeEvent {
+    pub seq: u64,
+    pub launch: Pubkey,
+    pub trader: Pubkey,
+    pub is_buy: bool,
+    pub amount: u64,
+    pub price: u64,
+    pub fee: u64,
+    // Lamports actually charged (buys) or paid out (sells), fees included
+    pub lamports: u64,
+    // Slippage bound the trader signed: max cost on buys, min proceeds on sells
+    pub limit: u64,
+    pub total_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DonationEvent {
+    pub launch: Pubkey,
+    pub donor: Pubkey,
+    pub lamports: u64,
+    pub memo: Option<String>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RaffleDrawnEvent {
+    pub launch: Pubkey,
+    pub winner: Pubkey,
+    pub prize: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct WithdrawalRequestedEvent {
+    pub launch: Pubkey,
+    pub amount: u64,
+    pub available_at: i64,
+}
+
+#[event]
+pub struct WithdrawalClaimedEvent {
+    pub launch: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenomicsCommittedEvent {
+    pub launch: Pubkey,
+    pub version: u32,
+    pub document_hash: [u8; 32],
+    pub previous_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeesClaimedEvent {
+    pub launch: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LaunchReportedEvent {
+    pub launch: Pubkey,
+    pub reported: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CurveRetunedEvent {
+    pub launch: Pubkey,
+    pub curve_ratio: u64,
+}
+
+#[event]
+pub struct FairMintFinalizedEvent {
+    pub launch: Pubkey,
+    pub deposits: u64,
+    pub allocated: u64,
+}
+
+#[event]
+pub struct LaunchCancelledEvent {
+    pub launch: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub reserves: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BuybackBurnedEvent {
+    pub lamports: u64,
+    pub tokens_burned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LaunchVaultToppedUpEvent {
+    pub launch: Pubkey,
+    pub lamports: u64,
+    pub reserves: u64,
+    pub timestamp: i64,
+}
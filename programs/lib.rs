@@ -1,210 +1,10191 @@
 // programs/meme_launcher/src/lib.rs
 
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 use anchor_spl::token::{self, Mint, Token, TokenAccount};
-use anchor_lang::solana_program::program::invoke;
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::{self, AssociatedToken};
+use anchor_spl::metadata::{self, mpl_token_metadata, Metadata};
+use spl_account_compression::program::SplAccountCompression;
+use spl_account_compression::Noop;
+use switchboard_on_demand::accounts::RandomnessAccountData;
+use std::collections::BTreeSet;
+
+pub mod curve;
+pub mod events;
+pub mod pda;
+pub use curve::*;
+use events::*;
+use pda::*;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+const MAX_MILESTONES: usize = 4;
+const PROPOSAL_VOTING_SECS: i64 = 3 * 24 * 60 * 60;
+const PROPOSAL_QUORUM_BPS: u16 = 1_000;
+// Spend caps tally per UTC day, and raising one only takes effect a day later
+const SPEND_CAP_DAY_SECS: i64 = 24 * 60 * 60;
+const MAX_NAME_LEN: usize = 32;
+const MAX_SYMBOL_LEN: usize = 10;
+const MAX_URI_LEN: usize = 200;
+const MAX_MEMO_LEN: usize = 100;
+// Native program whose SetComputeUnitPrice instruction carries a transaction's priority fee
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+// Switchboard On-Demand program, the only owner raffle randomness accounts are trusted from
+const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: Pubkey =
+    pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+// parent_name, owner and class pubkeys precede the name record's data
+const NAME_RECORD_HEADER_LEN: usize = 96;
+const RAFFLE_ENTRIES: usize = 16;
+const MAX_CURVE_STAGES: usize = 4;
+// Decimals of every launch mint. Instruction amounts are in base units (10^decimals per
+// token), while curve math works in whole tokens: curve_ratio is lamports per whole token
+// sold, per whole token of supply.
+pub const TOKEN_DECIMALS: u8 = 9;
+const MAX_BATCH_LAUNCHES: usize = 3;
+const MAX_REWARD_TIERS: usize = 8;
+const MAX_RESERVED_SYMBOLS: usize = 16;
+const MAX_FEE_TIERS: usize = 4;
+const MAX_FEE_RECIPIENTS: usize = 5;
+// Idempotency nonces each BuyerState remembers
+const RECENT_NONCES: usize = 8;
+// Permission bits a creator can grant a launch operator
+pub const OPERATOR_METADATA: u8 = 1 << 0;
+pub const OPERATOR_CIRCUIT_BREAKER: u8 = 1 << 1;
+pub const OPERATOR_FEE_WITHDRAWAL: u8 = 1 << 2;
+const OPERATOR_PERMISSIONS: u8 =
+    OPERATOR_METADATA | OPERATOR_CIRCUIT_BREAKER | OPERATOR_FEE_WITHDRAWAL;
+// launch, mint, mint_authority, creator_token_account, vault, stats, metadata
+const BATCH_ACCOUNTS_PER_LAUNCH: usize = 7;
+// token account, owner, owner's blacklist PDA, owner's BuyerState PDA, owner's protocol-token
+// ATA (or the default pubkey when they claim no holder discount)
+const SELL_BATCH_STRIDE: usize = 5;
+
 #[program]
 pub mod meme_launcher {
     use super::*;
 
-    pub fn initialize_launch(
-        ctx: Context<InitializeLaunch>,
-        name: String,
-        symbol: String,
-        initial_supply: u64,
-        curve_ratio: u64,
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        treasury: Pubkey,
+        protocol_fee_bps: u16,
+        sweep_bounty_bps: u16,
+        trade_fee_bps: u16,
+        deployment: DeploymentParams,
+    ) -> Result<()> {
+        require!(protocol_fee_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        require!(trade_fee_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        require!(sweep_bounty_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.treasury = treasury;
+        config.protocol_fee_bps = protocol_fee_bps;
+        config.sweep_bounty_bps = sweep_bounty_bps;
+        config.trade_fee_bps = trade_fee_bps;
+        config.graduation_dex_program = deployment.graduation_dex_program;
+        config.dlmm_program = deployment.dlmm_program;
+        config.buyback_dex_program = deployment.buyback_dex_program;
+        config.profile_program = deployment.profile_program;
+        config.name_service_program = deployment.name_service_program;
+        config.sol_tld_authority = deployment.sol_tld_authority;
+        config.bump = ctx.bumps.config;
+        Ok(())
+    }
+
+    // None charges sells the same as buys
+    pub fn set_sell_fee(ctx: Context<UpdateConfig>, sell_fee_bps: Option<u16>) -> Result<()> {
+        require!(sell_fee_bps.unwrap_or(0) <= MAX_BPS, LaunchError::InvalidFeeBps);
+        ctx.accounts.config.sell_fee_bps = sell_fee_bps;
+        Ok(())
+    }
+
+    // Per-launch buy and sell fees; None falls back to the Config fee for that side
+    pub fn set_launch_fee_override(
+        ctx: Context<SetLaunchFeeOverride>,
+        buy_fee_bps: Option<u16>,
+        sell_fee_bps: Option<u16>,
     ) -> Result<()> {
+        require!(
+            buy_fee_bps.unwrap_or(0) <= MAX_BPS && sell_fee_bps.unwrap_or(0) <= MAX_BPS,
+            LaunchError::InvalidFeeBps
+        );
         let launch = &mut ctx.accounts.launch;
-        launch.creator = ctx.accounts.creator.key();
-        launch.mint = ctx.accounts.mint.key();
-        launch.name = name;
-        launch.symbol = symbol;
-        launch.initial_supply = initial_supply;
-        launch.curve_ratio = curve_ratio;
-        launch.total_supply = initial_supply;
-        launch.is_active = true;
+        launch.buy_fee_bps_override = buy_fee_bps;
+        launch.sell_fee_bps_override = sell_fee_bps;
+        Ok(())
+    }
 
-        // Initialize token mint
-        let mint_authority = &[&[b"mint_authority", launch.key().as_ref(), &[ctx.bumps.mint_authority]]];
-        token::mint_to(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::MintTo {
-                    mint: ctx.accounts.mint.to_account_info(),
-                    to: ctx.accounts.creator_token_account.to_account_info(),
-                    authority: ctx.accounts.mint_authority.to_account_info(),
-                },
-                mint_authority,
-            ),
-            initial_supply,
+    // Share of this launch's protocol fees diverted to buying and burning the protocol token
+    pub fn set_launch_buyback(ctx: Context<SetLaunchFeeOverride>, buyback_bps: u16) -> Result<()> {
+        require!(buyback_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        ctx.accounts.launch.buyback_bps = buyback_bps;
+        Ok(())
+    }
+
+    // Flag a launch impersonating a known brand and freeze its metadata while the dispute
+    // resolves. Trading is unaffected; frontends read `reported` to warn users. Clearing the
+    // report lifts the freeze.
+    pub fn set_launch_reported(ctx: Context<SetLaunchReported>, reported: bool) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        launch.reported = reported;
+        launch.metadata_frozen = reported;
+
+        emit!(LaunchReportedEvent {
+            launch: launch.key(),
+            reported,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn configure_dynamic_fee(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+        max_fee_bps: u16,
+        window_secs: i64,
+        velocity_threshold: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if enabled {
+            require!(
+                max_fee_bps >= config.trade_fee_bps && max_fee_bps <= MAX_BPS,
+                LaunchError::InvalidFeeBps
+            );
+            require!(
+                window_secs > 0 && velocity_threshold > 0,
+                LaunchError::InvalidDynamicFeeParams
+            );
+        }
+
+        config.dynamic_fee_enabled = enabled;
+        config.max_dynamic_fee_bps = max_fee_bps;
+        config.velocity_window_secs = window_secs;
+        config.velocity_threshold = velocity_threshold;
+        Ok(())
+    }
+
+    pub fn set_attestation_mint(
+        ctx: Context<UpdateConfig>,
+        attestation_mint: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.config.attestation_mint = attestation_mint.unwrap_or_default();
+        Ok(())
+    }
+
+    // Traders holding at least `min_balance` of the protocol token pay `discount_bps` less on
+    // every trade. None turns the discount off.
+    pub fn set_protocol_token_discount(
+        ctx: Context<UpdateConfig>,
+        protocol_token_mint: Option<Pubkey>,
+        min_balance: u64,
+        discount_bps: u16,
+    ) -> Result<()> {
+        require!(discount_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        let config = &mut ctx.accounts.config;
+        config.protocol_token_mint = protocol_token_mint.unwrap_or_default();
+        config.protocol_discount_min_balance = min_balance;
+        config.protocol_discount_bps = discount_bps;
+        Ok(())
+    }
+
+    // DEX program execute_buyback may route swaps through; default disables buybacks
+    pub fn set_buyback_dex_program(ctx: Context<UpdateConfig>, dex_program: Pubkey) -> Result<()> {
+        ctx.accounts.config.buyback_dex_program = dex_program;
+        Ok(())
+    }
+
+    // Meteora DLMM program launches may graduate into instead of the graduation DEX, as a
+    // concentrated-liquidity position; default disables the option
+    pub fn set_dlmm_program(ctx: Context<UpdateConfig>, dlmm_program: Pubkey) -> Result<()> {
+        ctx.accounts.config.dlmm_program = dlmm_program;
+        Ok(())
+    }
+
+    // Program whose `[b"profile", wallet]` PDAs count as creator profiles; default disables
+    pub fn set_profile_program(ctx: Context<UpdateConfig>, profile_program: Pubkey) -> Result<()> {
+        ctx.accounts.config.profile_program = profile_program;
+        Ok(())
+    }
+
+    // SPL Name Service program and .sol TLD account set_creator_identity verifies domains
+    // against; default disables domain identities
+    pub fn set_name_service(
+        ctx: Context<UpdateConfig>,
+        name_service_program: Pubkey,
+        sol_tld_authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.name_service_program = name_service_program;
+        config.sol_tld_authority = sol_tld_authority;
+        Ok(())
+    }
+
+    pub fn set_withdrawal_timelock(ctx: Context<UpdateConfig>, timelock_secs: i64) -> Result<()> {
+        require!(timelock_secs >= 0, LaunchError::InvalidTimelock);
+        ctx.accounts.config.withdrawal_timelock_secs = timelock_secs;
+        Ok(())
+    }
+
+    // Admin rotation is two-step so a mistyped key cannot take over the config. The proposed
+    // admin may accept after `delay_secs`; proposing Pubkey::default() cancels.
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Pubkey, delay_secs: i64) -> Result<()> {
+        require!(delay_secs >= 0, LaunchError::InvalidTimelock);
+        let config = &mut ctx.accounts.config;
+        config.pending_admin = new_admin;
+        config.admin_accept_after = Clock::get()?.unix_timestamp.saturating_add(delay_secs);
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            Clock::get()?.unix_timestamp >= config.admin_accept_after,
+            LaunchError::AdminTransferTimelocked
+        );
+        config.admin = config.pending_admin;
+        config.pending_admin = Pubkey::default();
+        config.admin_accept_after = 0;
+        Ok(())
+    }
+
+    // Register the cold wallet once and re-point the treasury at the program's treasury PDA.
+    // From then on fees accrue in the PDA and can only ever be withdrawn to the cold wallet,
+    // so a compromised admin key cannot redirect them.
+    pub fn set_cold_wallet(ctx: Context<SetColdWallet>, cold_wallet: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.cold_wallet == Pubkey::default() && cold_wallet != Pubkey::default(),
+            LaunchError::ColdWalletAlreadySet
+        );
+        config.cold_wallet = cold_wallet;
+        config.treasury = ctx.accounts.treasury_vault.key();
+        Ok(())
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, lamports: u64) -> Result<()> {
+        // The vault keeps its rent-exempt minimum
+        let available = ctx
+            .accounts
+            .treasury_vault
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(lamports > 0 && lamports <= available, LaunchError::NothingToDistribute);
+
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.treasury_vault.key(),
+            &ctx.accounts.cold_wallet.key(),
+            lamports,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.treasury_vault.to_account_info(),
+                ctx.accounts.cold_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[TREASURY_SEED, &[ctx.bumps.treasury_vault]]],
         )?;
+        Ok(())
+    }
+
+    // Incident recovery: move treasury SOL into a launch's vault and count it as reserves, so
+    // refunds and sells can make holders whole without a program upgrade. Nothing moves back;
+    // the lamports leave through the launch's own refund, sell and graduation paths.
+    pub fn top_up_launch_vault(ctx: Context<TopUpLaunchVault>, lamports: u64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.state != LaunchState::Graduated && launch.state != LaunchState::Closed,
+            LaunchError::AlreadyGraduated
+        );
+        let available = ctx
+            .accounts
+            .treasury_vault
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(lamports > 0 && lamports <= available, LaunchError::NothingToDistribute);
+
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.treasury_vault.key(),
+            &ctx.accounts.vault.key(),
+            lamports,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.treasury_vault.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[TREASURY_SEED, &[ctx.bumps.treasury_vault]]],
+        )?;
+        launch.sol_reserves += lamports;
+        launch.treasury_top_ups += lamports;
 
+        emit!(LaunchVaultToppedUpEvent {
+            launch: launch.key(),
+            lamports,
+            reserves: launch.sol_reserves,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
-    pub fn buy_tokens(
-        ctx: Context<BuyTokens>,
+    // Share of the trading fee, in bps, paid to a frontend that routes a trade
+    pub fn set_frontend_fee(ctx: Context<UpdateConfig>, frontend_fee_bps: u16) -> Result<()> {
+        require!(frontend_fee_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        ctx.accounts.config.frontend_fee_bps = frontend_fee_bps;
+        Ok(())
+    }
+
+    // Window, in slots, within which a transaction's freshness proof must fall
+    pub fn set_max_tx_age(ctx: Context<UpdateConfig>, max_tx_age_slots: u64) -> Result<()> {
+        ctx.accounts.config.max_tx_age_slots = max_tx_age_slots;
+        Ok(())
+    }
+
+    // Proves the transaction was assembled after `slot` by quoting that slot's hash, which
+    // cannot be known in advance. Fresh-tx launches require this instruction alongside trades.
+    pub fn prove_freshness(ctx: Context<ProveFreshness>, slot: u64, slot_hash: [u8; 32]) -> Result<()> {
+        let current_slot = Clock::get()?.slot;
+        require!(
+            current_slot.saturating_sub(slot) <= ctx.accounts.config.max_tx_age_slots,
+            LaunchError::StaleTransaction
+        );
+
+        // SlotHashes is a u64 length followed by (slot, hash) entries, newest first
+        let data = ctx.accounts.slot_hashes.try_borrow_data()?;
+        let entries = data
+            .get(8..)
+            .ok_or(LaunchError::StaleTransaction)?
+            .chunks_exact(40);
+        for entry in entries {
+            let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            if entry_slot < slot {
+                break;
+            }
+            if entry_slot == slot && entry[8..] == slot_hash {
+                return Ok(());
+            }
+        }
+        err!(LaunchError::StaleTransaction)
+    }
+
+    // Hot launches can demand a freshness proof in every trading transaction
+    pub fn set_fresh_tx_mode(ctx: Context<SetFreshTxMode>, required: bool) -> Result<()> {
+        ctx.accounts.launch.fresh_tx_required = required;
+        Ok(())
+    }
+
+    // Minimums are in lamports and token base units (9 decimals); zero disables either check
+    pub fn set_min_trade_size(
+        ctx: Context<UpdateConfig>,
+        min_trade_lamports: u64,
+        min_trade_tokens: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.min_trade_lamports = min_trade_lamports;
+        config.min_trade_tokens = min_trade_tokens;
+        Ok(())
+    }
+
+    // Hook programs must be allow-listed before any launch can point its trade callbacks at them
+    pub fn allow_hook_program(ctx: Context<AllowHookProgram>, hook_program: Pubkey) -> Result<()> {
+        let entry = &mut ctx.accounts.hook_allowlist_entry;
+        entry.hook_program = hook_program;
+        entry.bump = ctx.bumps.hook_allowlist_entry;
+        Ok(())
+    }
+
+    pub fn disallow_hook_program(_ctx: Context<DisallowHookProgram>) -> Result<()> {
+        // Closing the entry stops every trade on launches that still use the hook
+        Ok(())
+    }
+
+    // Caps how far one slot's trades may move a launch's supply, as bps of its max supply,
+    // so a single whale transaction cannot reprice the curve out from under pending trades
+    pub fn set_max_supply_delta(ctx: Context<UpdateConfig>, max_supply_delta_bps: u16) -> Result<()> {
+        require!(max_supply_delta_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        ctx.accounts.config.max_supply_delta_bps = max_supply_delta_bps;
+        Ok(())
+    }
+
+    pub fn add_to_blacklist(ctx: Context<AddToBlacklist>, wallet: Pubkey) -> Result<()> {
+        let entry = &mut ctx.accounts.blacklist_entry;
+        entry.wallet = wallet;
+        entry.added_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.blacklist_entry;
+        Ok(())
+    }
+
+    pub fn remove_from_blacklist(_ctx: Context<RemoveFromBlacklist>) -> Result<()> {
+        // The entry is closed back to the admin by the close constraint
+        Ok(())
+    }
+
+    // Allow-listed market makers skip the max-wallet cap and use their own supply-delta
+    // limit (zero for none) once a launch has been trading for the configured window
+    pub fn add_market_maker(
+        ctx: Context<AddMarketMaker>,
+        wallet: Pubkey,
+        max_supply_delta_bps: u16,
+    ) -> Result<()> {
+        require!(max_supply_delta_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        let entry = &mut ctx.accounts.market_maker_entry;
+        entry.wallet = wallet;
+        entry.max_supply_delta_bps = max_supply_delta_bps;
+        entry.bump = ctx.bumps.market_maker_entry;
+        Ok(())
+    }
+
+    pub fn remove_market_maker(_ctx: Context<RemoveMarketMaker>) -> Result<()> {
+        // The entry is closed back to the admin by the close constraint
+        Ok(())
+    }
+
+    pub fn set_market_maker_window(ctx: Context<UpdateConfig>, window_secs: i64) -> Result<()> {
+        require!(window_secs >= 0, LaunchError::InvalidTimelock);
+        ctx.accounts.config.market_maker_window_secs = window_secs;
+        Ok(())
+    }
+
+    // Replace the list of symbols no launch may take, e.g. SOL, USDC or the protocol's own
+    // ticker. Matching is case-insensitive.
+    pub fn set_reserved_symbols(ctx: Context<UpdateConfig>, symbols: Vec<String>) -> Result<()> {
+        require!(symbols.len() <= MAX_RESERVED_SYMBOLS, LaunchError::InvalidSymbol);
+        let config = &mut ctx.accounts.config;
+        config.reserved_symbols = [[0u8; MAX_SYMBOL_LEN]; MAX_RESERVED_SYMBOLS];
+        for (slot, symbol) in config.reserved_symbols.iter_mut().zip(symbols.iter()) {
+            require!(
+                !symbol.is_empty() && symbol.len() <= MAX_SYMBOL_LEN,
+                LaunchError::InvalidSymbol
+            );
+            slot[..symbol.len()].copy_from_slice(symbol.to_ascii_uppercase().as_bytes());
+        }
+        config.reserved_symbol_count = symbols.len() as u8;
+        Ok(())
+    }
+
+    // Maturity discounts: once a launch's market cap reaches a tier's threshold, its trading
+    // fee drops by that tier's discount. Thresholds and discounts must both increase.
+    pub fn set_fee_tiers(ctx: Context<UpdateConfig>, tiers: Vec<FeeTier>) -> Result<()> {
+        require!(tiers.len() <= MAX_FEE_TIERS, LaunchError::InvalidFeeTiers);
+        let mut last = FeeTier::default();
+        for tier in tiers.iter() {
+            require!(
+                tier.market_cap_threshold > last.market_cap_threshold
+                    && tier.discount_bps > last.discount_bps
+                    && tier.discount_bps <= MAX_BPS,
+                LaunchError::InvalidFeeTiers
+            );
+            last = *tier;
+        }
+
+        let config = &mut ctx.accounts.config;
+        config.fee_tier_count = tiers.len() as u8;
+        config.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        config.fee_tiers[..tiers.len()].copy_from_slice(&tiers);
+        Ok(())
+    }
+
+    // Caps on what a launch may mint outside the curve and on its total supply. The initial
+    // supply is bounded both absolutely and as a share of max supply; zero leaves a cap off.
+    pub fn set_supply_caps(
+        ctx: Context<UpdateConfig>,
+        max_initial_supply: u64,
+        max_total_supply: u64,
+        max_initial_supply_bps: u16,
+    ) -> Result<()> {
+        require!(max_initial_supply_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        let config = &mut ctx.accounts.config;
+        config.max_initial_supply = max_initial_supply;
+        config.max_total_supply = max_total_supply;
+        config.max_initial_supply_bps = max_initial_supply_bps;
+        Ok(())
+    }
+
+    // Start a trading-competition epoch running until `ends_at`. Tracked buys and sells add
+    // their lamport volume to each wallet's BuyerState for the epoch; only one runs at a time.
+    pub fn open_competition(ctx: Context<OpenCompetition>, ends_at: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(ends_at > now, LaunchError::InvalidCompetition);
+        let config = &mut ctx.accounts.config;
+        require!(now >= config.competition_ends_at, LaunchError::CompetitionActive);
+        config.competition_epoch += 1;
+        config.competition_ends_at = ends_at;
+
+        let competition = &mut ctx.accounts.competition;
+        competition.epoch = config.competition_epoch;
+        competition.starts_at = now;
+        competition.ends_at = ends_at;
+        competition.bump = ctx.bumps.competition;
+        Ok(())
+    }
+
+    // Commit the leaderboard once the epoch is over: a merkle root over
+    // keccak(winner, prize lamports) leaves, paid from the competition vault
+    pub fn close_competition(
+        ctx: Context<CloseCompetition>,
+        winners_root: [u8; 32],
+        prize_lamports: u64,
+    ) -> Result<()> {
+        let competition = &mut ctx.accounts.competition;
+        require!(
+            Clock::get()?.unix_timestamp >= competition.ends_at,
+            LaunchError::CompetitionActive
+        );
+        require!(
+            competition.winners_root == [0u8; 32],
+            LaunchError::CompetitionSettled
+        );
+        require!(
+            ctx.accounts.competition_vault.lamports() >= prize_lamports,
+            LaunchError::InsufficientReserves
+        );
+        competition.winners_root = winners_root;
+        competition.prize_lamports = prize_lamports;
+        Ok(())
+    }
+
+    pub fn claim_competition_prize(
+        ctx: Context<ClaimCompetitionPrize>,
         amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let competition = &mut ctx.accounts.competition;
+        require!(
+            competition.winners_root != [0u8; 32],
+            LaunchError::CompetitionNotSettled
+        );
+        let leaf = keccak::hashv(&[ctx.accounts.winner.key().as_ref(), &amount.to_le_bytes()]).0;
+        require!(
+            verify_merkle_proof(&proof, competition.winners_root, leaf),
+            LaunchError::InvalidMerkleProof
+        );
+        competition.claimed = competition
+            .claimed
+            .checked_add(amount)
+            .filter(|claimed| *claimed <= competition.prize_lamports)
+            .ok_or(LaunchError::InvalidMerkleProof)?;
+
+        let claim = &mut ctx.accounts.prize_claim;
+        claim.competition = competition.key();
+        claim.winner = ctx.accounts.winner.key();
+        claim.amount = amount;
+        claim.bump = ctx.bumps.prize_claim;
+
+        let competition_key = competition.key();
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.competition_vault.key(),
+            &ctx.accounts.winner.key(),
+            amount,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.competition_vault.to_account_info(),
+                ctx.accounts.winner.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &[&[
+                COMPETITION_VAULT_SEED,
+                competition_key.as_ref(),
+                &[ctx.bumps.competition_vault],
+            ]],
+        )?;
+        Ok(())
+    }
+
+    // Bounds on the market cap creators may choose as their graduation target
+    pub fn set_graduation_bounds(
+        ctx: Context<UpdateConfig>,
+        min_market_cap: u64,
+        max_market_cap: u64,
+    ) -> Result<()> {
+        require!(min_market_cap <= max_market_cap, LaunchError::InvalidGraduationTarget);
+        let config = &mut ctx.accounts.config;
+        config.min_graduation_market_cap = min_market_cap;
+        config.max_graduation_market_cap = max_market_cap;
+        Ok(())
+    }
+
+    pub fn configure_graduation(
+        ctx: Context<UpdateConfig>,
+        min_cu_price: u64,
+        bounty: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.graduation_min_cu_price = min_cu_price;
+        config.graduation_bounty = bounty;
+        Ok(())
+    }
+
+    // Share of curve reserves seeded as DEX liquidity at graduation, and the DEX program.
+    // Non-zero routes every graduation through the chunked begin/deposit/finalize steps.
+    pub fn configure_graduation_liquidity(
+        ctx: Context<UpdateConfig>,
+        liquidity_bps: u16,
+        dex_program: Pubkey,
+    ) -> Result<()> {
+        require!(liquidity_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+        let config = &mut ctx.accounts.config;
+        config.graduation_liquidity_bps = liquidity_bps;
+        config.graduation_dex_program = dex_program;
+        Ok(())
+    }
+
+    // Creators earn each tier once their launch's lifetime volume crosses its threshold.
+    // SOL rewards are paid from the protocol rewards pool, token rewards from the launch's
+    // milestone pool, which anyone can fund by sending tokens to its associated account.
+    pub fn configure_reward_schedule(
+        ctx: Context<ConfigureRewardSchedule>,
+        tiers: Vec<RewardTier>,
     ) -> Result<()> {
+        require!(tiers.len() <= MAX_REWARD_TIERS, LaunchError::InvalidRewardSchedule);
+        let mut last_threshold = 0;
+        for tier in tiers.iter() {
+            require!(
+                tier.volume_threshold > last_threshold,
+                LaunchError::InvalidRewardSchedule
+            );
+            last_threshold = tier.volume_threshold;
+        }
+
+        let schedule = &mut ctx.accounts.reward_schedule;
+        schedule.tier_count = tiers.len() as u8;
+        schedule.tiers = [RewardTier::default(); MAX_REWARD_TIERS];
+        schedule.tiers[..tiers.len()].copy_from_slice(&tiers);
+        schedule.bump = ctx.bumps.reward_schedule;
+        Ok(())
+    }
+
+    pub fn initialize_launch(ctx: Context<InitializeLaunch>, params: LaunchParams) -> Result<()> {
         let launch = &mut ctx.accounts.launch;
-        require!(launch.is_active, LaunchError::LaunchInactive);
+        launch.init(
+            &ctx.accounts.config,
+            ctx.accounts.creator.key(),
+            ctx.accounts.mint.key(),
+            params,
+        )?;
 
-        // Calculate price based on bonding curve
-        let price = calculate_price(launch.total_supply, amount, launch.curve_ratio)?;
-        
-        // Transfer SOL from buyer to creator
+        let stats = &mut ctx.accounts.stats;
+        stats.launch = launch.key();
+        stats.window_start = Clock::get()?.unix_timestamp;
+        stats.bump = ctx.bumps.stats;
+
+        // Fund the vault with its rent-exempt minimum so the first buy can be any size
         let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.buyer.key(),
-            &launch.creator,
-            price,
+            &ctx.accounts.payer.key(),
+            &ctx.accounts.vault.key(),
+            Rent::get()?.minimum_balance(0),
         );
         invoke(
             &transfer_ix,
             &[
-                ctx.accounts.buyer.to_account_info(),
-                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
                 ctx.accounts.system_program.to_account_info(),
             ],
         )?;
 
-        // Mint tokens to buyer
-        let mint_authority = &[&[b"mint_authority", launch.key().as_ref(), &[ctx.bumps.mint_authority]]];
+        // Initialize token mint
+        let launch_key = launch.key();
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
         token::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 token::MintTo {
                     mint: ctx.accounts.mint.to_account_info(),
-                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    to: ctx.accounts.creator_token_account.to_account_info(),
                     authority: ctx.accounts.mint_authority.to_account_info(),
                 },
                 mint_authority,
             ),
-            amount,
+            launch.initial_supply,
+        )?;
+
+        create_token_metadata(
+            launch,
+            ctx.accounts.token_metadata_program.to_account_info(),
+            metadata::CreateMetadataAccountsV3 {
+                metadata: ctx.accounts.metadata.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                mint_authority: ctx.accounts.mint_authority.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                update_authority: ctx.accounts.mint_authority.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                // The v3 instruction no longer reads the rent sysvar
+                rent: ctx.accounts.system_program.to_account_info(),
+            },
+            mint_authority,
         )?;
 
-        launch.total_supply += amount;
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(name: String, symbol: String)]
-pub struct InitializeLaunch<'info> {
-    #[account(init, payer = creator, space = Launch::LEN)]
-    pub launch: Account<'info, Launch>,
-    
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    
-    #[account(
-        init,
-        payer = creator,
-        mint::decimals = 9,
-        mint::authority = mint_authority,
-    )]
-    pub mint: Account<'info, Mint>,
-    
-    /// CHECK: PDA for mint authority
-    #[account(
-        seeds = [b"mint_authority", launch.key().as_ref()],
-        bump
-    )]
-    pub mint_authority: UncheckedAccount<'info>,
-    
-    #[account(
-        init,
-        payer = creator,
-        associated_token::mint = mint,
-        associated_token::authority = creator,
-    )]
-    pub creator_token_account: Account<'info, TokenAccount>,
-    
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    // Dry run of launch creation for UIs: simulate it to get the same errors without
+    // creating any accounts
+    pub fn validate_launch_params(ctx: Context<ValidateLaunchParams>, params: LaunchParams) -> Result<()> {
+        params.validate(&ctx.accounts.config)
+    }
 
-#[derive(Accounts)]
-pub struct BuyTokens<'info> {
-    #[account(mut)]
-    pub launch: Account<'info, Launch>,
-    
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-    
-    /// CHECK: Creator account to receive SOL
-    #[account(mut)]
-    pub creator: UncheckedAccount<'info>,
-    
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
-    
-    /// CHECK: PDA for mint authority
-    #[account(
-        seeds = [b"mint_authority", launch.key().as_ref()],
-        bump
-    )]
-    pub mint_authority: UncheckedAccount<'info>,
-    
+    // Create up to MAX_BATCH_LAUNCHES related launches atomically, e.g. a token and its
+    // governance token. Each launch passes BATCH_ACCOUNTS_PER_LAUNCH remaining accounts.
+    pub fn initialize_launch_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, InitializeLaunchBatch<'info>>,
+        launches: Vec<LaunchParams>,
+    ) -> Result<()> {
+        require!(
+            !launches.is_empty() && launches.len() <= MAX_BATCH_LAUNCHES,
+            LaunchError::InvalidBatch
+        );
+        require!(
+            ctx.remaining_accounts.len() == launches.len() * BATCH_ACCOUNTS_PER_LAUNCH,
+            LaunchError::InvalidBatch
+        );
+
+        // Every launch in the batch links to the first so clients can group them
+        let bundle_leader = ctx.remaining_accounts[0].key();
+        for (params, group) in launches
+            .into_iter()
+            .zip(ctx.remaining_accounts.chunks(BATCH_ACCOUNTS_PER_LAUNCH))
+        {
+            create_batch_launch(ctx.accounts, ctx.program_id, group, params, bundle_leader)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn trade(
+        ctx: Context<Trade>,
+        direction: TradeDirection,
+        amount: u64,
+        limit: u64,
+        payout_mode: PayoutMode,
+        memo: Option<String>,
+    ) -> Result<TradeResult> {
+        // `limit` is the max lamports spent on buys and the min lamports received on sells.
+        // Buys are charged the cost computed at execution, never `limit` itself.
+        // `payout_mode` only affects sells and `memo` only buys.
+        match direction {
+            TradeDirection::Buy => process_buy(ctx, amount, limit, memo),
+            TradeDirection::Sell => process_sell(ctx, amount, limit, payout_mode),
+        }
+    }
+
+    // Escrow a block of tokens for a peer-to-peer sale that never touches the curve
+    pub fn create_otc_offer(ctx: Context<CreateOtcOffer>, amount: u64, ask_lamports: u64) -> Result<()> {
+        require!(amount > 0 && ask_lamports > 0, LaunchError::InvalidOtcOffer);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.seller_token_account.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                    authority: ctx.accounts.seller.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let offer = &mut ctx.accounts.offer;
+        offer.launch = ctx.accounts.launch.key();
+        offer.seller = ctx.accounts.seller.key();
+        offer.amount = amount;
+        offer.ask_lamports = ask_lamports;
+        offer.bump = ctx.bumps.offer;
+        Ok(())
+    }
+
+    pub fn accept_otc_offer(ctx: Context<AcceptOtcOffer>) -> Result<()> {
+        require_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+        let offer = &ctx.accounts.offer;
+        let fee = fee_of(offer.ask_lamports, ctx.accounts.config.protocol_fee_bps)?;
+
+        // The buyer pays the ask, split between the seller and the protocol treasury
+        for (recipient, lamports) in [
+            (ctx.accounts.seller.to_account_info(), offer.ask_lamports - fee),
+            (ctx.accounts.treasury.to_account_info(), fee),
+        ] {
+            if lamports == 0 {
+                continue;
+            }
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.buyer.key(),
+                recipient.key,
+                lamports,
+            );
+            invoke(
+                &transfer_ix,
+                &[
+                    ctx.accounts.buyer.to_account_info(),
+                    recipient,
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        release_otc_escrow(
+            offer,
+            &ctx.accounts.escrow,
+            &ctx.accounts.buyer_token_account,
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.token_program,
+        )
+    }
+
+    pub fn cancel_otc_offer(ctx: Context<CancelOtcOffer>) -> Result<()> {
+        // Tokens go back to the seller; the offer is closed by its constraint
+        release_otc_escrow(
+            &ctx.accounts.offer,
+            &ctx.accounts.escrow,
+            &ctx.accounts.seller_token_account,
+            &ctx.accounts.seller.to_account_info(),
+            &ctx.accounts.token_program,
+        )
+    }
+
+    // Unclaimed airdrop tokens can be reclaimed once `claim_window_secs` passes; zero keeps
+    // the airdrop open indefinitely
+    pub fn relaunch(
+        ctx: Context<Relaunch>,
+        airdrop_supply: u64,
+        claim_window_secs: i64,
+    ) -> Result<()> {
+        require!(claim_window_secs >= 0, LaunchError::InvalidTimelock);
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply
+                && launch.previous_launch == Pubkey::default(),
+            LaunchError::TradingAlreadyStarted
+        );
+        require_keys_neq!(
+            ctx.accounts.original_launch.key(),
+            launch.key(),
+            LaunchError::InvalidRelaunch
+        );
+        launch.previous_launch = ctx.accounts.original_launch.key();
+
+        // Honor a holder snapshot of the original launch with an airdrop escrowed on v2
+        if let Some(snapshot) = ctx.accounts.original_snapshot.as_ref() {
+            require!(airdrop_supply > 0, LaunchError::InvalidRelaunch);
+            let new_supply = launch
+                .total_supply
+                .checked_add(airdrop_supply)
+                .ok_or(LaunchError::MaxSupplyExceeded)?;
+            require!(new_supply <= launch.max_supply, LaunchError::MaxSupplyExceeded);
+
+            let airdrop_escrow = ctx
+                .accounts
+                .airdrop_escrow
+                .as_ref()
+                .ok_or(LaunchError::InvalidRelaunch)?;
+            let launch_key = launch.key();
+            let mint_authority: &[&[&[u8]]] =
+                &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::MintTo {
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: airdrop_escrow.to_account_info(),
+                        authority: ctx.accounts.mint_authority.to_account_info(),
+                    },
+                    mint_authority,
+                ),
+                airdrop_supply,
+            )?;
+
+            launch.airdrop_root = snapshot.merkle_root;
+            launch.airdrop_supply = airdrop_supply;
+            if claim_window_secs > 0 {
+                launch.airdrop_expires_at =
+                    Clock::get()?.unix_timestamp.saturating_add(claim_window_secs);
+            }
+            // The airdrop was not bought on the curve, so it joins the initial supply that cannot
+            // be sold back rather than drawing on reserves it never paid into
+            launch.initial_supply = new_supply;
+            launch.total_supply = new_supply;
+        }
+        Ok(())
+    }
+
+    pub fn claim_airdrop(ctx: Context<ClaimAirdrop>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            !launch.airdrop_expired(Clock::get()?.unix_timestamp),
+            LaunchError::AirdropExpired
+        );
+        let leaf = keccak::hashv(&[ctx.accounts.claimant.key().as_ref(), &amount.to_le_bytes()]).0;
+        require!(
+            verify_merkle_proof(&proof, launch.airdrop_root, leaf),
+            LaunchError::InvalidMerkleProof
+        );
+
+        let claimed = launch
+            .airdrop_claimed
+            .checked_add(amount)
+            .filter(|claimed| *claimed <= launch.airdrop_supply)
+            .ok_or(LaunchError::InvalidMerkleProof)?;
+        launch.airdrop_claimed = claimed;
+
+        let claim = &mut ctx.accounts.airdrop_claim;
+        claim.launch = launch.key();
+        claim.claimant = ctx.accounts.claimant.key();
+        claim.amount = amount;
+        claim.bump = ctx.bumps.airdrop_claim;
+
+        let launch_key = launch.key();
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.airdrop_escrow.to_account_info(),
+                    to: ctx.accounts.claimant_token_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                mint_authority,
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    // After expiry the creator takes back whatever the airdrop escrow still holds, or burns it
+    pub fn reclaim_unclaimed(ctx: Context<ReclaimUnclaimed>, burn: bool) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.airdrop_expired(Clock::get()?.unix_timestamp),
+            LaunchError::AirdropNotExpired
+        );
+        let remaining = launch.airdrop_supply - launch.airdrop_claimed;
+        require!(remaining > 0, LaunchError::NothingToClaim);
+        launch.airdrop_claimed = launch.airdrop_supply;
+
+        let launch_key = launch.key();
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+        if burn {
+            token::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: ctx.accounts.mint.to_account_info(),
+                        from: ctx.accounts.airdrop_escrow.to_account_info(),
+                        authority: ctx.accounts.mint_authority.to_account_info(),
+                    },
+                    mint_authority,
+                ),
+                remaining,
+            )?;
+            // Burned airdrop leaves the unsellable initial supply it was booked under
+            launch.initial_supply -= remaining;
+            launch.total_supply -= remaining;
+        } else {
+            let creator_token_account = ctx
+                .accounts
+                .creator_token_account
+                .as_ref()
+                .ok_or(LaunchError::InvalidTokenAccount)?;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.airdrop_escrow.to_account_info(),
+                        to: creator_token_account.to_account_info(),
+                        authority: ctx.accounts.mint_authority.to_account_info(),
+                    },
+                    mint_authority,
+                ),
+                remaining,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // `memo` is forwarded to the SPL Memo program, e.g. for custodial deposit attribution
+    // `nonce` makes retries safe: a buy repeating one of the wallet's last RECENT_NONCES
+    // nonces succeeds without buying again and returns the launch's current state. Nonces are
+    // stored in the BuyerState, which must be passed with one.
+    pub fn buy_tokens(
+        ctx: Context<Trade>,
+        amount: u64,
+        memo: Option<String>,
+        nonce: Option<u64>,
+    ) -> Result<TradeResult> {
+        if let Some(nonce) = nonce {
+            require!(nonce != 0, LaunchError::InvalidNonce);
+            let holder = ctx
+                .accounts
+                .buyer_state
+                .as_deref_mut()
+                .ok_or(LaunchError::InvalidNonce)?;
+            if holder.recent_nonces.contains(&nonce) {
+                let launch = &ctx.accounts.launch;
+                return Ok(TradeResult {
+                    amount: 0,
+                    lamports: 0,
+                    fee: 0,
+                    spot_price: launch.spot_price()?,
+                    total_supply: launch.total_supply,
+                    remaining_supply: launch.max_supply - launch.total_supply,
+                });
+            }
+            holder.remember_nonce(nonce);
+        }
+        process_buy(ctx, amount, u64::MAX, memo)
+    }
+
+    pub fn sell_tokens(ctx: Context<Trade>, amount: u64) -> Result<TradeResult> {
+        process_sell(ctx, amount, 0, PayoutMode::Native)
+    }
+
+    // Read-only valuation for lending integrations: what selling `amount` into the curve
+    // right now would pay out, after price impact and the undiscounted sell fee. Returned
+    // as return data next to the spot value, so the haircut between the two is explicit.
+    pub fn get_collateral_value(
+        ctx: Context<GetCollateralValue>,
+        amount: u64,
+    ) -> Result<CollateralValue> {
+        let launch = &ctx.accounts.launch;
+        launch.require_curve_open()?;
+        let new_supply = launch
+            .total_supply
+            .checked_sub(amount)
+            .filter(|supply| *supply >= launch.initial_supply)
+            .ok_or(LaunchError::InsufficientReserves)?;
+
+        let proceeds = launch.curve_cost(new_supply, amount, Rounding::Down)?;
+        // The fee window is not rolled here, so a stale window can only overstate the fee
+        let fee_bps = ctx.accounts.config.trade_fee_bps(launch, false, &ctx.accounts.stats, 0)?;
+        let value = (proceeds - fee_of(proceeds, fee_bps)?).min(launch.sol_reserves);
+        let spot_value =
+            (launch.spot_price()? as u128 * amount as u128 / launch.token_unit() as u128) as u64;
+        let haircut_bps = match spot_value {
+            0 => 0,
+            _ => {
+                let retained = value as u128 * MAX_BPS as u128 / spot_value as u128;
+                MAX_BPS - retained.min(MAX_BPS as u128) as u16
+            }
+        };
+        Ok(CollateralValue { amount, spot_value, value, haircut_bps })
+    }
+
+    // Sell one launch's tokens and buy another's in a single instruction. The instruction's
+    // Trade accounts are the sell side; remaining_accounts are a second Trade account list,
+    // in the same order, for the launch being bought. The sell proceeds fund the buy, which
+    // may cost at most `max_added_lamports` more; any surplus stays with the trader.
+    pub fn swap_between_launches<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Trade<'info>>,
+        sell_amount: u64,
+        buy_amount: u64,
+        max_added_lamports: u64,
+    ) -> Result<SwapResult> {
+        let program_id = ctx.program_id;
+        let trader = ctx.accounts.trader.key();
+        let sold_launch = ctx.accounts.launch.key();
+        let mut buy_infos = ctx.remaining_accounts;
+        let sold = process_sell(ctx, sell_amount, 0, PayoutMode::Native)?;
+
+        let mut bumps = TradeBumps::default();
+        let mut buy_accounts =
+            Trade::try_accounts(program_id, &mut buy_infos, &[], &mut bumps, &mut BTreeSet::new())?;
+        require_keys_neq!(buy_accounts.launch.key(), sold_launch, LaunchError::SwapSameLaunch);
+        require_keys_eq!(buy_accounts.trader.key(), trader, LaunchError::Unauthorized);
+
+        let max_cost = sold
+            .lamports
+            .checked_add(max_added_lamports)
+            .ok_or(LaunchError::InvalidPriceCalculation)?;
+        let bought = process_buy(
+            Context::new(program_id, &mut buy_accounts, buy_infos, bumps),
+            buy_amount,
+            max_cost,
+            None,
+        )?;
+        // Anchor only persists the instruction's own accounts, so write the buy side back here
+        buy_accounts.exit(program_id)?;
+        Ok(SwapResult { sold, bought })
+    }
+
+    // Sell out of many holders' token accounts in one transaction, each up to the amount its
+    // owner approved the signing delegate for. remaining_accounts are passed as
+    // [token account, owner, owner's blacklist PDA, owner's BuyerState PDA, owner's
+    // protocol-token ATA] groups; every owner receives their own proceeds. The ATA slot may be
+    // the default pubkey for owners without a holder discount. Each sell goes through the same
+    // quote, checks and holder bookkeeping as a single sell.
+    pub fn sell_for_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SellForMany<'info>>,
+        min_total_proceeds: u64,
+    ) -> Result<TradeResult> {
+        require!(
+            !ctx.remaining_accounts.is_empty()
+                && ctx.remaining_accounts.chunks_exact(SELL_BATCH_STRIDE).remainder().is_empty(),
+            LaunchError::InvalidBatch
+        );
+        require_fresh_tx(&ctx.accounts.launch, ctx.accounts.instructions.as_ref())?;
+        let now = Clock::get()?.unix_timestamp;
+        let launch = &mut ctx.accounts.launch;
+        require!(!launch.trade_in_flight, LaunchError::ReentrantTrade);
+        launch.check_mint_supply(&ctx.accounts.mint)?;
+        let config = &ctx.accounts.config;
+        let stats = &mut ctx.accounts.stats;
+        require!(now >= stats.sells_paused_until, LaunchError::SellsPaused);
+        stats.roll_window(now, config.velocity_window_secs);
+
+        // Quote and record every sell first so state is committed before any CPI
+        let mut sells = Vec::with_capacity(ctx.remaining_accounts.len() / SELL_BATCH_STRIDE);
+        let mut total_amount: u64 = 0;
+        let mut total_proceeds: u64 = 0;
+        let mut total_fees: u64 = 0;
+        for group in ctx.remaining_accounts.chunks(SELL_BATCH_STRIDE) {
+            let [token_info, owner, blacklist_entry, holder_info, discount_info] = group else {
+                return err!(LaunchError::InvalidBatch);
+            };
+            let token_account = read_trader_account(token_info, false)?;
+            require_keys_eq!(token_account.mint, launch.mint, LaunchError::InvalidTokenAccount);
+            require_keys_eq!(token_account.owner, *owner.key, LaunchError::InvalidTokenAccount);
+            require_keys_eq!(
+                *blacklist_entry.key,
+                find_blacklist_address(owner.key).0,
+                LaunchError::InvalidBatch
+            );
+            require_not_blacklisted(blacklist_entry)?;
+            require!(
+                token_account.delegate == COption::Some(ctx.accounts.delegate.key()),
+                LaunchError::Unauthorized
+            );
+            let amount = token_account.delegated_amount.min(token_account.amount);
+            if amount == 0 {
+                continue;
+            }
+
+            // Only the owner's own slot counts, so one holder's ATA can't discount another's sell
+            let discount_bps =
+                config.holder_discount_bps(owner.key, std::slice::from_ref(discount_info))?;
+            let (new_supply, proceeds, fee) = quote_checked_sell(
+                launch,
+                stats,
+                config,
+                amount,
+                config.max_supply_delta_bps,
+                discount_bps,
+                now,
+            )?;
+            launch.record_sell(new_supply, proceeds);
+            stats.record_trade(proceeds);
+
+            // A holder's BuyerState is passed whether or not it exists, so the basis a cancelled
+            // launch refunds cannot be kept by leaving it out
+            require_keys_eq!(
+                *holder_info.key,
+                find_buyer_state_address(&launch.key(), owner.key).0,
+                LaunchError::InvalidBatch
+            );
+            if !holder_info.data_is_empty() {
+                let mut holder = Account::<BuyerState>::try_from(holder_info)?;
+                record_holder_sell(launch, config, &mut holder, amount, proceeds, now);
+                holder.exit(ctx.program_id)?;
+            }
+            total_amount += amount;
+            total_proceeds = total_proceeds.saturating_add(proceeds - fee);
+            total_fees += fee;
+
+            emit!(TradeEvent {
+                seq: launch.next_trade_seq(),
+                launch: launch.key(),
+                trader: token_account.owner,
+                is_buy: false,
+                amount,
+                price: proceeds,
+                fee,
+                lamports: proceeds - fee,
+                limit: 0,
+                total_supply: launch.total_supply,
+                timestamp: now,
+            });
+            let result = TradeResult {
+                amount,
+                lamports: proceeds - fee,
+                fee,
+                spot_price: launch.spot_price()?,
+                total_supply: launch.total_supply,
+                remaining_supply: launch.max_supply - launch.total_supply,
+            };
+            sells.push((token_info, owner, result));
+        }
+        require!(total_proceeds >= min_total_proceeds, LaunchError::SlippageExceeded);
+        stats.track_price(launch, launch.spot_price()?, now)?;
+        launch.accrued_protocol_fees += total_fees;
+        launch.trade_in_flight = true;
+        launch.exit(ctx.program_id)?;
+        stats.exit(ctx.program_id)?;
+
+        let launch_key = launch.key();
+        let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+        for (token_info, owner, result) in sells.iter() {
+            token::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Burn {
+                        mint: ctx.accounts.mint.to_account_info(),
+                        from: (*token_info).clone(),
+                        authority: ctx.accounts.delegate.to_account_info(),
+                    },
+                ),
+                result.amount,
+            )?;
+            if result.lamports == 0 {
+                continue;
+            }
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault.key(),
+                owner.key,
+                result.lamports,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    (*owner).clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                vault_seeds,
+            )?;
+        }
+
+        launch.trade_in_flight = false;
+        for (_, owner, result) in sells {
+            invoke_trade_hook(
+                &ctx.accounts.launch,
+                ctx.accounts.hook_program.as_ref(),
+                ctx.accounts.hook_allowlist_entry.as_deref(),
+                owner.clone(),
+                false,
+                result,
+            )?;
+        }
+        let launch = &ctx.accounts.launch;
+        Ok(TradeResult {
+            amount: total_amount,
+            lamports: total_proceeds,
+            fee: total_fees,
+            spot_price: launch.spot_price()?,
+            total_supply: launch.total_supply,
+            remaining_supply: launch.max_supply - launch.total_supply,
+        })
+    }
+
+    pub fn close_trade_receipt(_ctx: Context<CloseTradeReceipt>) -> Result<()> {
+        // Rent is returned to the buyer by the close constraint
+        Ok(())
+    }
+
+    pub fn set_milestones(ctx: Context<SetMilestones>, milestones: Vec<Milestone>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply && launch.milestone_count == 0,
+            LaunchError::MilestonesLocked
+        );
+        require!(launch.stream_slots == 0, LaunchError::InvalidPayoutStream);
+        require!(
+            !milestones.is_empty() && milestones.len() <= MAX_MILESTONES,
+            LaunchError::InvalidMilestones
+        );
+
+        let mut total_bps: u16 = 0;
+        let mut last_unlock = 0;
+        for milestone in milestones.iter() {
+            require!(
+                milestone.unlock_after_secs >= last_unlock && milestone.bps > 0,
+                LaunchError::InvalidMilestones
+            );
+            last_unlock = milestone.unlock_after_secs;
+            total_bps = total_bps
+                .checked_add(milestone.bps)
+                .ok_or(LaunchError::InvalidMilestones)?;
+        }
+        require!(total_bps == MAX_BPS, LaunchError::InvalidMilestones);
+
+        for (slot, milestone) in launch.milestones.iter_mut().zip(milestones.iter()) {
+            *slot = *milestone;
+        }
+        launch.milestone_count = milestones.len() as u8;
+        Ok(())
+    }
+
+    // Alternative to milestones: the raise unlocks linearly over `stream_slots` slots after
+    // graduation and can be claimed at any point with claim_stream
+    pub fn configure_payout_stream(ctx: Context<SetMilestones>, stream_slots: u64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply && launch.stream_slots == 0,
+            LaunchError::MilestonesLocked
+        );
+        require!(
+            stream_slots > 0 && launch.milestone_count == 0,
+            LaunchError::InvalidPayoutStream
+        );
+        launch.stream_slots = stream_slots;
+        Ok(())
+    }
+
+    pub fn configure_crowdfund(
+        ctx: Context<ConfigureCrowdfund>,
+        raise_target: u64,
+        raise_deadline: i64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply && launch.raise_target == 0,
+            LaunchError::CrowdfundLocked
+        );
+        require!(
+            raise_target > 0 && raise_deadline > Clock::get()?.unix_timestamp,
+            LaunchError::InvalidCrowdfundParams
+        );
+
+        launch.raise_target = raise_target;
+        launch.raise_deadline = raise_deadline;
+        Ok(())
+    }
+
+    pub fn pledge(ctx: Context<Pledge>, amount: u64) -> Result<()> {
+        ctx.accounts.config.verify_attestation(
+            ctx.accounts.attestation_token_account.as_deref(),
+            &ctx.accounts.buyer.key(),
+        )?;
+        ctx.accounts.launch.verify_holder_gate(
+            ctx.accounts.gate_token_account.as_deref(),
+            &ctx.accounts.buyer.key(),
+        )?;
+
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.crowdfund_pending(), LaunchError::CrowdfundNotPending);
+        require!(
+            Clock::get()?.unix_timestamp < launch.raise_deadline,
+            LaunchError::CrowdfundExpired
+        );
+        let (new_supply, price) = launch.quote_allocation(amount)?;
+
+        // Escrow the pledge in the launch vault; tokens are minted once the target is met
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.vault.key(),
+            price,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let buyer_state = &mut ctx.accounts.buyer_state;
+        buyer_state.launch = launch.key();
+        buyer_state.buyer = ctx.accounts.buyer.key();
+        buyer_state.bump = ctx.bumps.buyer_state;
+        buyer_state.pledged_lamports = buyer_state.pledged_lamports.saturating_add(price);
+        buyer_state.pledged_tokens = buyer_state.pledged_tokens.saturating_add(amount);
+
+        launch.raised = launch.raised.saturating_add(price);
+        launch.unminted_supply += amount;
+        launch.record_buy(new_supply, price)?;
+        Ok(())
+    }
+
+    pub fn claim_pledge(ctx: Context<ClaimPledge>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(!launch.crowdfund_pending(), LaunchError::CrowdfundPending);
+
+        let buyer_state = &mut ctx.accounts.buyer_state;
+        let amount = buyer_state.pledged_tokens;
+        require!(amount > 0, LaunchError::NothingToClaim);
+        let pledged_lamports = buyer_state.pledged_lamports;
+        launch.add_cost_basis(buyer_state, pledged_lamports, amount);
+        buyer_state.pledged_tokens = 0;
+        buyer_state.pledged_lamports = 0;
+        launch.unminted_supply -= amount;
+
+        let launch_key = launch.key();
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                mint_authority,
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn refund_pledge(ctx: Context<RefundPledge>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.crowdfund_failed(Clock::get()?.unix_timestamp),
+            LaunchError::CrowdfundNotFailed
+        );
+
+        let buyer_state = &mut ctx.accounts.buyer_state;
+        let lamports = buyer_state.pledged_lamports;
+        require!(lamports > 0, LaunchError::NothingToClaim);
+        buyer_state.pledged_lamports = 0;
+        buyer_state.pledged_tokens = 0;
+        if launch.state != LaunchState::Refunding {
+            launch.transition(LaunchState::Refunding)?;
+        }
+        launch.sol_reserves = launch.sol_reserves.saturating_sub(lamports);
+
+        let launch_key = launch.key();
+        let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.buyer.key(),
+            lamports,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            vault_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    // Fair mint: buyers deposit SOL until `ends_at`, then finalize_allocation sells up to
+    // `supply` tokens to all of them at one uniform price, pro rata to their deposits
+    pub fn configure_fair_mint(
+        ctx: Context<ConfigureCrowdfund>,
+        supply: u64,
+        ends_at: i64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(
+            launch.fair_mint_ends_at == 0
+                && supply > 0
+                && supply <= launch.max_supply - launch.total_supply
+                && ends_at > Clock::get()?.unix_timestamp,
+            LaunchError::InvalidFairMint
+        );
+
+        launch.fair_mint_supply = supply;
+        launch.fair_mint_ends_at = ends_at;
+        Ok(())
+    }
+
+    pub fn deposit_fair_mint(ctx: Context<Pledge>, lamports: u64) -> Result<()> {
+        ctx.accounts.config.verify_attestation(
+            ctx.accounts.attestation_token_account.as_deref(),
+            &ctx.accounts.buyer.key(),
+        )?;
+        ctx.accounts.launch.verify_holder_gate(
+            ctx.accounts.gate_token_account.as_deref(),
+            &ctx.accounts.buyer.key(),
+        )?;
+
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.fair_mint_ends_at > 0 && Clock::get()?.unix_timestamp < launch.fair_mint_ends_at,
+            LaunchError::FairMintClosed
+        );
+        require!(lamports > 0, LaunchError::InvalidFairMint);
+
+        // Deposits wait in the vault, outside sol_reserves, until the allocation is finalized
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.buyer.key(),
+            &ctx.accounts.vault.key(),
+            lamports,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let buyer_state = &mut ctx.accounts.buyer_state;
+        buyer_state.launch = launch.key();
+        buyer_state.buyer = ctx.accounts.buyer.key();
+        buyer_state.bump = ctx.bumps.buyer_state;
+        buyer_state.fair_mint_lamports = buyer_state.fair_mint_lamports.saturating_add(lamports);
+
+        launch.fair_mint_deposits = launch.fair_mint_deposits.saturating_add(lamports);
+        Ok(())
+    }
+
+    // Permissionless crank once the window closes. The allocation is as many tokens as the
+    // deposits pay for on the curve, capped at the fair-mint supply, so reserves always cover
+    // selling them back; an oversubscribed mint simply clears at a higher uniform price.
+    pub fn finalize_allocation(ctx: Context<FinalizeAllocation>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.fair_mint_ends_at > 0 && !launch.fair_mint_finalized,
+            LaunchError::InvalidFairMint
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= launch.fair_mint_ends_at,
+            LaunchError::FairMintPending
+        );
+
+        let deposits = launch.fair_mint_deposits;
+        let allocated = launch.affordable_amount(deposits, launch.fair_mint_supply)?;
+        launch.fair_mint_finalized = true;
+        launch.fair_mint_allocated = allocated;
+        if allocated > 0 {
+            launch.unminted_supply += allocated;
+            let new_supply = launch.total_supply + allocated;
+            launch.record_buy(new_supply, deposits)?;
+        }
+
+        emit!(FairMintFinalizedEvent {
+            launch: launch.key(),
+            deposits,
+            allocated,
+        });
+        Ok(())
+    }
+
+    // Mint the buyer's pro-rata share of a finalized fair mint, or refund the deposit if the
+    // pooled deposits could not buy a single base unit
+    pub fn claim_fair_mint(ctx: Context<ClaimFairMint>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.fair_mint_finalized, LaunchError::FairMintPending);
+
+        let buyer_state = &mut ctx.accounts.buyer_state;
+        let deposit = buyer_state.fair_mint_lamports;
+        require!(deposit > 0, LaunchError::NothingToClaim);
+        buyer_state.fair_mint_lamports = 0;
+
+        let launch_key = launch.key();
+        if launch.fair_mint_allocated == 0 {
+            let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault.key(),
+                &ctx.accounts.buyer.key(),
+                deposit,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    ctx.accounts.buyer.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                vault_seeds,
+            )?;
+            return Ok(());
+        }
+
+        // Rounding dust stays unminted, leaving reserves slightly over-collateralized
+        let amount = (deposit as u128 * launch.fair_mint_allocated as u128
+            / launch.fair_mint_deposits as u128) as u64;
+        launch.unminted_supply -= amount;
+        launch.add_cost_basis(buyer_state, deposit, amount);
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                mint_authority,
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    // Abort a launch before its curve completes. Trading stops and holders tracked by a
+    // BuyerState can reclaim their cost basis with claim_refund. Blocked while pledges or
+    // fair-mint shares are unclaimed, since those refund through their own flows.
+    pub fn cancel_launch(ctx: Context<CancelLaunch>) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            authority == launch.creator || authority == ctx.accounts.config.admin,
+            LaunchError::Unauthorized
+        );
+        require!(
+            launch.unminted_supply == 0
+                && (launch.fair_mint_ends_at == 0 || launch.fair_mint_finalized),
+            LaunchError::CancelBlocked
+        );
+        launch.transition(LaunchState::Cancelled)?;
+
+        emit!(LaunchCancelledEvent {
+            launch: launch.key(),
+            cancelled_by: authority,
+            reserves: launch.sol_reserves,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Burn the buyer's curve-bought tokens from a cancelled launch and return what they paid
+    // for them. If sells at a profit left reserves short of the tracked total, every refund is
+    // scaled down by the same ratio. A buyer holding only part of their tracked tokens is
+    // refunded that share and can claim the rest later; the BuyerState is closed to the buyer
+    // once no tracked tokens remain.
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.state == LaunchState::Cancelled, LaunchError::LaunchNotCancelled);
+
+        let holder = &mut ctx.accounts.buyer_state;
+        require!(holder.basis_tokens > 0, LaunchError::NothingToClaim);
+        let burned = ctx.accounts.buyer_token_account.amount.min(holder.basis_tokens);
+        require!(burned > 0, LaunchError::NothingToClaim);
+        let refund = launch.refund_basis(holder, burned);
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            burned,
+        )?;
+        launch.total_supply -= burned;
+        launch.sol_reserves -= refund;
+        launch.refunded_lamports += refund;
+
+        let launch_key = launch.key();
+        let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.buyer.key(),
+            refund,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            vault_seeds,
+        )?;
+
+        if ctx.accounts.buyer_state.basis_tokens == 0 {
+            ctx.accounts.buyer_state.close(ctx.accounts.buyer.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    // Close the loyalty round: points stop accruing now and `lamports` are split across
+    // holders in proportion to the time-weighted balance each accumulated
+    pub fn fund_loyalty_rewards(ctx: Context<FundLoyaltyRewards>, lamports: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.loyalty_ends_at == 0 && lamports > 0, LaunchError::InvalidLoyaltyRound);
+        launch.accrue_loyalty(now);
+        require!(launch.loyalty_points > 0, LaunchError::InvalidLoyaltyRound);
+        launch.loyalty_ends_at = now;
+        launch.loyalty_reward_lamports = lamports;
+
+        // The pool also keeps its rent-exempt minimum so partial claims never strand it
+        let deposit = lamports
+            .checked_add(Rent::get()?.minimum_balance(0))
+            .ok_or(LaunchError::InvalidLoyaltyRound)?;
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.loyalty_pool.key(),
+            deposit,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.loyalty_pool.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    // The first `limit` unique buyers whose trades carry a BuyerState are numbered as early
+    // supporters and can each claim a badge PDA
+    pub fn configure_early_supporters(
+        ctx: Context<ConfigureEarlySupporters>,
+        limit: u16,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        launch.early_supporter_limit = limit;
+        Ok(())
+    }
+
+    // Buys that start below `cutoff_supply` and carry a BuyerState earn one LP claim unit per
+    // lamport spent. After graduation the units share whatever LP fee revenue is paid into
+    // the launch's LP reward pool. Zero turns the units off.
+    pub fn configure_lp_claim_units(
+        ctx: Context<ConfigureEarlySupporters>,
+        cutoff_supply: u64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(cutoff_supply <= launch.max_supply, LaunchError::InvalidMaxSupply);
+        launch.lp_unit_cutoff_supply = cutoff_supply;
+        Ok(())
+    }
+
+    // Pay LP fee revenue collected from the graduated pool into the LP reward pool. Anyone may
+    // fund it, e.g. the LP locker's fee crank; it can be topped up any number of times.
+    pub fn fund_lp_rewards(ctx: Context<FundLpRewards>, lamports: u64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.state == LaunchState::Graduated, LaunchError::NotGraduated);
+        require!(launch.total_lp_units > 0 && lamports > 0, LaunchError::NothingToDistribute);
+
+        // The first deposit also covers the pool's rent-exempt minimum
+        let mut deposit = lamports;
+        if ctx.accounts.lp_reward_pool.lamports() == 0 {
+            deposit = deposit
+                .checked_add(Rent::get()?.minimum_balance(0))
+                .ok_or(LaunchError::InvalidPriceCalculation)?;
+        }
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.funder.key(),
+            &ctx.accounts.lp_reward_pool.key(),
+            deposit,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.funder.to_account_info(),
+                ctx.accounts.lp_reward_pool.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        launch.lp_rewards_funded = launch.lp_rewards_funded.saturating_add(lamports);
+        Ok(())
+    }
+
+    // Pay the buyer's pro-rata share of everything funded so far, less what they already took
+    pub fn claim_lp_rewards(ctx: Context<ClaimLpRewards>) -> Result<()> {
+        let launch = &ctx.accounts.launch;
+        require!(launch.state == LaunchState::Graduated, LaunchError::NotGraduated);
+        let holder = &mut ctx.accounts.buyer_state;
+        require!(holder.lp_units > 0, LaunchError::NothingToClaim);
+
+        let entitled = (launch.lp_rewards_funded as u128 * holder.lp_units as u128
+            / launch.total_lp_units as u128) as u64;
+        let reward = entitled.saturating_sub(holder.lp_rewards_claimed);
+        require!(reward > 0, LaunchError::NothingToClaim);
+        holder.lp_rewards_claimed = entitled;
+
+        let launch_key = launch.key();
+        let pool_seeds: &[&[&[u8]]] =
+            &[&[LP_REWARD_POOL_SEED, launch_key.as_ref(), &[ctx.bumps.lp_reward_pool]]];
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.lp_reward_pool.key(),
+            &ctx.accounts.buyer.key(),
+            reward,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.lp_reward_pool.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            pool_seeds,
+        )?;
+        Ok(())
+    }
+
+    // Daily lamport cap on the wallet's buys of this launch, including fees; zero removes it.
+    // Lowering or adding a cap applies at once, but a raise waits SPEND_CAP_DAY_SECS so a
+    // stolen delegate key cannot lift the cap and spend in the same breath.
+    pub fn set_spend_cap(ctx: Context<SetSpendCap>, daily_cap: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let holder = &mut ctx.accounts.buyer_state;
+        holder.launch = ctx.accounts.launch.key();
+        holder.buyer = ctx.accounts.buyer.key();
+        holder.bump = ctx.bumps.buyer_state;
+        holder.apply_pending_spend_cap(now);
+
+        let tightens = daily_cap != 0
+            && (holder.daily_spend_cap == 0 || daily_cap <= holder.daily_spend_cap);
+        if tightens {
+            holder.daily_spend_cap = daily_cap;
+            holder.pending_spend_cap = 0;
+            holder.pending_spend_cap_at = 0;
+        } else {
+            holder.pending_spend_cap = daily_cap;
+            holder.pending_spend_cap_at = now + SPEND_CAP_DAY_SECS;
+        }
+        Ok(())
+    }
+
+    pub fn claim_early_supporter_badge(ctx: Context<ClaimEarlySupporterBadge>) -> Result<()> {
+        let number = ctx.accounts.buyer_state.supporter_number;
+        require!(number > 0, LaunchError::NotEarlySupporter);
+
+        let badge = &mut ctx.accounts.badge;
+        badge.launch = ctx.accounts.launch.key();
+        badge.holder = ctx.accounts.buyer.key();
+        badge.number = number;
+        badge.bump = ctx.bumps.badge;
+        Ok(())
+    }
+
+    pub fn claim_loyalty_reward(ctx: Context<ClaimLoyaltyReward>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        let ends_at = launch.loyalty_ends_at;
+        require!(ends_at > 0, LaunchError::InvalidLoyaltyRound);
+
+        let holder = &mut ctx.accounts.buyer_state;
+        require!(!holder.loyalty_claimed, LaunchError::NothingToClaim);
+        let balance = holder.loyalty_balance;
+        launch.sync_loyalty(holder, balance, ends_at);
+        holder.loyalty_claimed = true;
+
+        let reward: u64 = (launch.loyalty_reward_lamports as u128 * holder.loyalty_points
+            / launch.loyalty_points)
+            .try_into()
+            .map_err(|_| LaunchError::InvalidPriceCalculation)?;
+        require!(reward > 0, LaunchError::NothingToClaim);
+
+        let launch_key = launch.key();
+        let pool_seeds: &[&[&[u8]]] =
+            &[&[LOYALTY_POOL_SEED, launch_key.as_ref(), &[ctx.bumps.loyalty_pool]]];
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.loyalty_pool.key(),
+            &ctx.accounts.buyer.key(),
+            reward,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.loyalty_pool.to_account_info(),
+                ctx.accounts.buyer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            pool_seeds,
+        )?;
+        Ok(())
+    }
+
+    // Limit buyers to holders of at least `min_balance` of `gate_mint`, e.g. the protocol token
+    // or a community NFT. Pass the default pubkey to remove the gate.
+    pub fn configure_holder_gate(
+        ctx: Context<ConfigureMaxWallet>,
+        gate_mint: Pubkey,
+        min_balance: u64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(
+            gate_mint == Pubkey::default() || min_balance > 0,
+            LaunchError::InvalidHolderGate
+        );
+
+        launch.gate_mint = gate_mint;
+        launch.gate_min_balance = min_balance;
+        Ok(())
+    }
+
+    pub fn configure_max_wallet(
+        ctx: Context<ConfigureMaxWallet>,
+        max_wallet_bps: u16,
+        enforcement_secs: i64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(
+            max_wallet_bps > 0 && max_wallet_bps <= MAX_BPS && enforcement_secs >= 0,
+            LaunchError::InvalidMaxWallet
+        );
+
+        launch.max_wallet_bps = max_wallet_bps;
+        launch.max_wallet_until = if enforcement_secs == 0 {
+            i64::MAX
+        } else {
+            Clock::get()?.unix_timestamp.saturating_add(enforcement_secs)
+        };
+        Ok(())
+    }
+
+    // Pause sells for `pause_secs` whenever the spot price falls more than `drop_bps` below
+    // its peak within a `window_secs` rolling window. Buys are never paused.
+    pub fn configure_circuit_breaker(
+        ctx: Context<ConfigureCircuitBreaker>,
+        drop_bps: u16,
+        window_secs: i64,
+        pause_secs: i64,
+    ) -> Result<()> {
+        require!(
+            drop_bps > 0 && drop_bps < MAX_BPS && window_secs > 0 && pause_secs > 0,
+            LaunchError::InvalidCircuitBreaker
+        );
+        let launch = &mut ctx.accounts.launch;
+        launch.require_authority(&ctx.accounts.authority.key(), OPERATOR_CIRCUIT_BREAKER)?;
+        launch.breaker_drop_bps = drop_bps;
+        launch.breaker_window_secs = window_secs;
+        launch.breaker_pause_secs = pause_secs;
+        Ok(())
+    }
+
+    pub fn configure_curve_stages(
+        ctx: Context<ConfigureCurveStages>,
+        stages: Vec<CurveStage>,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(stages.len() <= MAX_CURVE_STAGES, LaunchError::InvalidCurveStages);
+
+        let mut last_threshold = launch.initial_supply;
+        for stage in stages.iter() {
+            require!(
+                stage.supply_threshold > last_threshold
+                    && stage.supply_threshold <= launch.max_supply
+                    && stage.multiplier_bps > MAX_BPS,
+                LaunchError::InvalidCurveStages
+            );
+            last_threshold = stage.supply_threshold;
+        }
+
+        launch.curve_stages = [CurveStage::default(); MAX_CURVE_STAGES];
+        for (slot, stage) in launch.curve_stages.iter_mut().zip(stages.iter()) {
+            *slot = *stage;
+        }
+        launch.curve_stage_count = stages.len() as u8;
+        Ok(())
+    }
+
+    // Past `flat_sale_start` the remaining supply sells at the curve price frozen at that
+    // point, so a raise can finish without ever-steepening prices. Zero disables the mode.
+    pub fn configure_flat_sale(ctx: Context<ConfigureFlatSale>, flat_sale_start: u64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(
+            flat_sale_start == 0
+                || (flat_sale_start > launch.initial_supply && flat_sale_start < launch.max_supply),
+            LaunchError::InvalidFlatSale
+        );
+
+        launch.flat_sale_start = flat_sale_start;
+        Ok(())
+    }
+
+    // Choose the DEX the graduation liquidity is seeded into, any time before graduation
+    pub fn configure_graduation_venue(
+        ctx: Context<ConfigureGraduationVenue>,
+        venue: GraduationVenue,
+    ) -> Result<()> {
+        ctx.accounts.launch.set_graduation_venue(&ctx.accounts.config, venue)
+    }
+
+    // Trade-to-earn: rebate `rebate_bps` of each buy's fee as launch tokens, up to `emission_cap`
+    pub fn configure_rebate(
+        ctx: Context<ConfigureRebate>,
+        rebate_bps: u16,
+        emission_cap: u64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(rebate_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+
+        launch.rebate_bps = rebate_bps;
+        launch.rebate_emission_cap = emission_cap;
+        Ok(())
+    }
+
+    // Fix a misconfigured curve slope without abandoning the launch and its mint. Only allowed
+    // before any trade, and before reservations or pledges move supply, so every token in
+    // circulation was priced on the curve now in force.
+    pub fn retune_curve(ctx: Context<RetuneCurve>, curve_ratio: u64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            ctx.accounts.stats.trade_count == 0 && launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(curve_ratio > 0, LaunchError::InvalidCurveRatio);
+        // Same overflow guard launch creation applies to the last token
+        calculate_price(
+            launch.max_supply,
+            launch.token_unit(),
+            curve_ratio,
+            launch.decimals,
+            Rounding::Up,
+        )
+        .ok_or(LaunchError::InvalidPriceCalculation)?;
+
+        launch.curve_ratio = curve_ratio;
+        emit!(CurveRetunedEvent {
+            launch: launch.key(),
+            curve_ratio,
+        });
+        Ok(())
+    }
+
+    // Switch to a constant-product curve over virtual reserves, matching pump.fun pricing.
+    // Stages and the flat sale do not apply to this curve type.
+    pub fn configure_curve_type(
+        ctx: Context<ConfigureCurveType>,
+        curve_type: CurveType,
+        virtual_sol_reserves: u64,
+        virtual_token_reserves: u64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        // A bootstrap deposit is already folded into the virtual reserves
+        require!(launch.bootstrap_lamports == 0, LaunchError::InvalidBootstrapDeposit);
+        if curve_type == CurveType::VirtualReserves {
+            // The virtual token side must outlast every token the curve can sell
+            require!(
+                virtual_sol_reserves > 0
+                    && virtual_token_reserves > launch.max_supply - launch.initial_supply,
+                LaunchError::InvalidVirtualReserves
+            );
+        }
+
+        launch.curve_type = curve_type;
+        launch.virtual_sol_reserves = virtual_sol_reserves;
+        launch.virtual_token_reserves = virtual_token_reserves;
+        Ok(())
+    }
+
+    // Creator SOL added to the virtual SOL side before trading, raising the starting price.
+    // It is held in the vault outside sol_reserves, so it never backs sells or counts as
+    // raised funds, and goes back to the creator when the launch graduates.
+    pub fn deposit_bootstrap_liquidity(
+        ctx: Context<DepositBootstrapLiquidity>,
+        lamports: u64,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(
+            lamports > 0 && launch.curve_type == CurveType::VirtualReserves,
+            LaunchError::InvalidBootstrapDeposit
+        );
+
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.creator.key(),
+            &ctx.accounts.vault.key(),
+            lamports,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.creator.to_account_info(),
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        launch.virtual_sol_reserves = launch
+            .virtual_sol_reserves
+            .checked_add(lamports)
+            .ok_or(LaunchError::InvalidVirtualReserves)?;
+        launch.bootstrap_lamports += lamports;
+        Ok(())
+    }
+
+    pub fn configure_reservations(ctx: Context<ConfigureReservations>, opens_at: i64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            launch.total_supply == launch.initial_supply && launch.trading_opens_at == 0,
+            LaunchError::TradingAlreadyStarted
+        );
+        require!(
+            opens_at > Clock::get()?.unix_timestamp,
+            LaunchError::InvalidReservationWindow
+        );
+        launch.base_token_price()?;
+
+        launch.trading_opens_at = opens_at;
+        Ok(())
+    }
+
+    pub fn reserve(ctx: Context<Reserve>, lamports: u64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            Clock::get()?.unix_timestamp < launch.trading_opens_at,
+            LaunchError::ReservationsClosed
+        );
+        // Enough to fill at least one base unit
+        require!(
+            lamports >= launch.base_token_price()?.div_ceil(launch.token_unit()),
+            LaunchError::InvalidReservationAmount
+        );
+
+        let reservation = &mut ctx.accounts.reservation;
+        reservation.launch = launch.key();
+        reservation.index = launch.reservation_count;
+        reservation.owner = ctx.accounts.owner.key();
+        reservation.lamports = lamports;
+        reservation.bump = ctx.bumps.reservation;
+
+        // Escrow the reservation in its own PDA until it is settled
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.owner.key(),
+            &reservation.key(),
+            lamports,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.owner.to_account_info(),
+                reservation.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        launch.reservation_count = launch
+            .reservation_count
+            .checked_add(1)
+            .ok_or(LaunchError::InvalidReservationAmount)?;
+        Ok(())
+    }
+
+    pub fn settle_reservations<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleReservations<'info>>,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            Clock::get()?.unix_timestamp >= launch.trading_opens_at,
+            LaunchError::TradingNotStarted
+        );
+        require!(
+            !ctx.remaining_accounts.is_empty()
+                && ctx.remaining_accounts.chunks_exact(3).remainder().is_empty(),
+            LaunchError::InvalidReservation
+        );
+
+        let launch_key = launch.key();
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+
+        // Each reservation is passed as [reservation, owner, owner token account], in queue order
+        for chunk in ctx.remaining_accounts.chunks(3) {
+            let reservation = Account::<Reservation>::try_from(&chunk[0])?;
+            require_keys_eq!(reservation.launch, launch_key, LaunchError::InvalidReservation);
+            require!(
+                reservation.index == launch.reservations_settled,
+                LaunchError::InvalidReservation
+            );
+            require_keys_eq!(*chunk[1].key, reservation.owner, LaunchError::InvalidReservation);
+
+            let owner_token_account = Account::<TokenAccount>::try_from(&chunk[2])?;
+            require_keys_eq!(owner_token_account.mint, launch.mint, LaunchError::InvalidReservation);
+            require_keys_eq!(owner_token_account.owner, reservation.owner, LaunchError::InvalidReservation);
+
+            let (amount, cost) = launch.fill_reservation(reservation.lamports)?;
+            if amount > 0 {
+                token::mint_to(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::MintTo {
+                            mint: ctx.accounts.mint.to_account_info(),
+                            to: chunk[2].clone(),
+                            authority: ctx.accounts.mint_authority.to_account_info(),
+                        },
+                        mint_authority,
+                    ),
+                    amount,
+                )?;
+
+                **chunk[0].try_borrow_mut_lamports()? -= cost;
+                **ctx.accounts.vault.try_borrow_mut_lamports()? += cost;
+            }
+
+            // Unfilled lamports and rent go back to the owner
+            reservation.close(chunk[1].clone())?;
+            launch.reservations_settled += 1;
+        }
+
+        Ok(())
+    }
+
+    // Flag a launch as high security: its metadata updates, graduation and vault withdrawals
+    // then also need `authority` to sign. Replacing or clearing the authority needs its
+    // signature too, so the creator cannot drop it alone.
+    pub fn set_secondary_authority(ctx: Context<SetSecondaryAuthority>, authority: Pubkey) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+        launch.secondary_authority = authority;
+        Ok(())
+    }
+
+    // Delegate day-to-day management to an operator hot key, scoped by OPERATOR_* bits. The
+    // creator keeps ownership; withdrawals an operator requests still pay the fee
+    // destination. Pass the default pubkey to remove the operator.
+    pub fn set_operator(
+        ctx: Context<SetSecondaryAuthority>,
+        operator: Pubkey,
+        permissions: u8,
+    ) -> Result<()> {
+        require!(
+            permissions & !OPERATOR_PERMISSIONS == 0,
+            LaunchError::InvalidOperatorPermissions
+        );
+        let launch = &mut ctx.accounts.launch;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+        launch.operator = operator;
+        launch.operator_permissions = if operator == Pubkey::default() { 0 } else { permissions };
+        Ok(())
+    }
+
+    // Split the creator's fees across up to MAX_FEE_RECIPIENTS wallets by bps weight. The
+    // launch's fee destination becomes the FeeSplit PDA, so proceeds collect there until
+    // claim_fee_split pays them out. Weights must sum to MAX_BPS.
+    pub fn configure_fee_split(
+        ctx: Context<ConfigureFeeSplit>,
+        recipients: Vec<FeeRecipient>,
+    ) -> Result<()> {
+        require!(
+            !recipients.is_empty() && recipients.len() <= MAX_FEE_RECIPIENTS,
+            LaunchError::InvalidFeeSplit
+        );
+        let total: u32 = recipients.iter().map(|r| r.bps as u32).sum();
+        require!(total == MAX_BPS as u32, LaunchError::InvalidFeeSplit);
+
+        let launch = &mut ctx.accounts.launch;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+        let split = &mut ctx.accounts.fee_split;
+        split.launch = launch.key();
+        split.recipient_count = recipients.len() as u8;
+        split.recipients = [FeeRecipient::default(); MAX_FEE_RECIPIENTS];
+        split.recipients[..recipients.len()].copy_from_slice(&recipients);
+        split.bump = ctx.bumps.fee_split;
+        launch.fee_destination = split.key();
+        Ok(())
+    }
+
+    // Pay everything the FeeSplit holds above its rent-exempt minimum to its recipients in one
+    // call. Recipients are passed as remaining accounts in the order they were configured;
+    // rounding dust stays behind for the next claim.
+    pub fn claim_fee_split<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimFeeSplit<'info>>,
+    ) -> Result<()> {
+        let split = &ctx.accounts.fee_split;
+        let recipients = &split.recipients[..split.recipient_count as usize];
+        require!(
+            ctx.remaining_accounts.len() == recipients.len(),
+            LaunchError::AccountMismatch
+        );
+
+        let split_info = split.to_account_info();
+        let balance = split_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(FeeSplit::LEN));
+        require!(balance > 0, LaunchError::NothingToDistribute);
+        for (recipient, account) in recipients.iter().zip(ctx.remaining_accounts) {
+            require_keys_eq!(account.key(), recipient.wallet, LaunchError::AccountMismatch);
+            let share = bps_of(balance, recipient.bps)?;
+            **split_info.try_borrow_mut_lamports()? -= share;
+            **account.try_borrow_mut_lamports()? += share;
+        }
+        Ok(())
+    }
+
+    // Link the launch to a .sol name record or profile PDA the creator owns, checked now so
+    // wallets can show the name with on-chain proof. Ownership can move later, so the link
+    // records when it was verified; passing no account clears it.
+    pub fn set_creator_identity(
+        ctx: Context<SetCreatorIdentity>,
+        kind: CreatorIdentityKind,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(!launch.metadata_frozen, LaunchError::MetadataFrozen);
+
+        let Some(identity) = ctx.accounts.identity.as_ref() else {
+            launch.creator_identity = Pubkey::default();
+            launch.creator_identity_kind = CreatorIdentityKind::None;
+            launch.creator_identity_verified_at = 0;
+            return Ok(());
+        };
+        match kind {
+            CreatorIdentityKind::None => return err!(LaunchError::InvalidCreatorIdentity),
+            CreatorIdentityKind::SolDomain => {
+                let config = &ctx.accounts.config;
+                require!(
+                    config.name_service_program != Pubkey::default()
+                        && *identity.owner == config.name_service_program,
+                    LaunchError::InvalidCreatorIdentity
+                );
+                let data = identity.try_borrow_data()?;
+                require!(
+                    data.len() >= NAME_RECORD_HEADER_LEN,
+                    LaunchError::InvalidCreatorIdentity
+                );
+                let parent = Pubkey::try_from(&data[0..32]).unwrap();
+                let owner = Pubkey::try_from(&data[32..64]).unwrap();
+                require_keys_eq!(
+                    parent,
+                    config.sol_tld_authority,
+                    LaunchError::InvalidCreatorIdentity
+                );
+                require_keys_eq!(owner, launch.creator, LaunchError::InvalidCreatorIdentity);
+            }
+            CreatorIdentityKind::Profile => {
+                let profile_program = ctx.accounts.config.profile_program;
+                require!(
+                    profile_program != Pubkey::default() && *identity.owner == profile_program,
+                    LaunchError::InvalidCreatorIdentity
+                );
+                let (expected, _) = Pubkey::find_program_address(
+                    &[b"profile", launch.creator.as_ref()],
+                    &profile_program,
+                );
+                require_keys_eq!(identity.key(), expected, LaunchError::InvalidCreatorIdentity);
+            }
+        }
+
+        launch.creator_identity = identity.key();
+        launch.creator_identity_kind = kind;
+        launch.creator_identity_verified_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    pub fn set_metadata_uri(
+        ctx: Context<SetMetadataUri>,
+        uri: String,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(uri.len() <= MAX_URI_LEN, LaunchError::UriTooLong);
+        let launch = &ctx.accounts.launch;
+        launch.require_authority(&ctx.accounts.authority.key(), OPERATOR_METADATA)?;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+
+        // Once committed, the URI may only move to a copy of the same content, unless holders
+        // approved the new content hash through governance. Immutable launches committed theirs
+        // at creation and never change.
+        let launch = &mut ctx.accounts.launch;
+        require!(!launch.metadata_frozen, LaunchError::MetadataFrozen);
+        require!(!launch.immutable_metadata, LaunchError::MetadataImmutable);
+        if launch.metadata_hash != [0u8; 32] && content_hash != launch.metadata_hash {
+            require!(
+                content_hash == launch.approved_metadata_hash,
+                LaunchError::MetadataHashMismatch
+            );
+            launch.approved_metadata_hash = [0u8; 32];
+        }
+
+        launch.metadata_uri = uri;
+        launch.metadata_hash = content_hash;
+
+        // Mirror the URI into the Metaplex metadata
+        let launch_key = launch.key();
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+        metadata::update_metadata_accounts_v2(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                metadata::UpdateMetadataAccountsV2 {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    update_authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                mint_authority,
+            ),
+            None,
+            Some(token_metadata_data(launch)),
+            None,
+            None,
+        )?;
+        Ok(())
+    }
+
+    // Commits the hash of the creator's description and tokenomics document (supply split,
+    // vesting terms). Each commit gets its own revision PDA so earlier versions stay on chain;
+    // launches with immutable metadata can commit once and never replace it.
+    pub fn commit_tokenomics(ctx: Context<CommitTokenomics>, document_hash: [u8; 32]) -> Result<()> {
+        let launch = &ctx.accounts.launch;
+        launch.require_authority(&ctx.accounts.authority.key(), OPERATOR_METADATA)?;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+
+        let launch = &mut ctx.accounts.launch;
+        require!(!launch.metadata_frozen, LaunchError::MetadataFrozen);
+        require!(
+            !(launch.immutable_metadata && launch.tokenomics_version > 0),
+            LaunchError::MetadataImmutable
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let revision = &mut ctx.accounts.revision;
+        revision.launch = launch.key();
+        revision.version = launch.tokenomics_version;
+        revision.document_hash = document_hash;
+        revision.previous_hash = launch.tokenomics_hash;
+        revision.committed_at = now;
+        revision.bump = ctx.bumps.revision;
+
+        launch.tokenomics_hash = document_hash;
+        launch.tokenomics_version = launch
+            .tokenomics_version
+            .checked_add(1)
+            .ok_or(LaunchError::InvalidPriceCalculation)?;
+
+        emit!(TokenomicsCommittedEvent {
+            launch: launch.key(),
+            version: revision.version,
+            document_hash,
+            previous_hash: revision.previous_hash,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    // Trade records are appended as leaves of a concurrent merkle tree instead of one PDA each.
+    // The tree account is allocated by the client, owned by the compression program.
+    pub fn init_trade_tree(
+        ctx: Context<InitTradeTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.trade_tree == Pubkey::default(), LaunchError::InvalidTradeTree);
+        launch.trade_tree = ctx.accounts.trade_tree.key();
+
+        let launch_key = launch.key();
+        let tree_authority_seeds: &[&[&[u8]]] =
+            &[&[TREE_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.tree_authority]]];
+        spl_account_compression::cpi::init_empty_merkle_tree(
+            CpiContext::new_with_signer(
+                ctx.accounts.compression_program.to_account_info(),
+                spl_account_compression::cpi::accounts::Initialize {
+                    merkle_tree: ctx.accounts.trade_tree.to_account_info(),
+                    authority: ctx.accounts.tree_authority.to_account_info(),
+                    noop: ctx.accounts.noop_program.to_account_info(),
+                },
+                tree_authority_seeds,
+            ),
+            max_depth,
+            max_buffer_size,
+        )
+    }
+
+    // Mint a one-of-one deed for the launch. Whoever holds it can take over the creator role
+    // with `assume_creator`, so launch ownership can be sold or escrowed by moving the NFT.
+    pub fn mint_deed(ctx: Context<MintDeed>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.deed_mint == Pubkey::default(), LaunchError::DeedAlreadyMinted);
+        launch.deed_mint = ctx.accounts.deed_mint.key();
+
+        let launch_key = launch.key();
+        let mint_authority: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.deed_mint.to_account_info(),
+                    to: ctx.accounts.creator_deed_account.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                mint_authority,
+            ),
+            1,
+        )?;
+
+        // Drop the mint authority so the deed can never be duplicated
+        token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::SetAuthority {
+                    current_authority: ctx.accounts.mint_authority.to_account_info(),
+                    account_or_mint: ctx.accounts.deed_mint.to_account_info(),
+                },
+                mint_authority,
+            ),
+            token::spl_token::instruction::AuthorityType::MintTokens,
+            None,
+        )
+    }
+
+    pub fn assume_creator(ctx: Context<AssumeCreator>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        let holder = ctx.accounts.holder.key();
+
+        // Fees follow the deed unless governance already pointed them elsewhere
+        if launch.fee_destination == launch.creator {
+            launch.fee_destination = holder;
+        }
+        launch.creator = holder;
+        Ok(())
+    }
+
+    // Pass Pubkey::default() to remove the hook
+    pub fn set_hook_program(ctx: Context<SetHookProgram>, hook_program: Pubkey) -> Result<()> {
+        if hook_program != Pubkey::default() {
+            let entry = ctx
+                .accounts
+                .hook_allowlist_entry
+                .as_ref()
+                .ok_or(LaunchError::HookNotAllowed)?;
+            require_keys_eq!(entry.hook_program, hook_program, LaunchError::HookNotAllowed);
+        }
+
+        ctx.accounts.launch.hook_program = hook_program;
+        Ok(())
+    }
+
+    pub fn claim_milestone_reward(ctx: Context<ClaimMilestoneReward>) -> Result<()> {
+        let schedule = &ctx.accounts.reward_schedule;
+        let stats = &mut ctx.accounts.stats;
+
+        // Sum every newly crossed tier so a creator can claim several at once
+        let mut lamports: u64 = 0;
+        let mut tokens: u64 = 0;
+        let mut claimed = stats.reward_tiers_claimed;
+        while claimed < schedule.tier_count {
+            let tier = schedule.tiers[claimed as usize];
+            if stats.total_volume < tier.volume_threshold {
+                break;
+            }
+            lamports = lamports.saturating_add(tier.lamports);
+            tokens = tokens.saturating_add(tier.tokens);
+            claimed += 1;
+        }
+        require!(claimed > stats.reward_tiers_claimed, LaunchError::NoRewardAvailable);
+        stats.reward_tiers_claimed = claimed;
+
+        if lamports > 0 {
+            // The pool keeps its rent-exempt minimum so it stays alive for other launches
+            let pool = &ctx.accounts.rewards_pool;
+            let available = pool.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+            require!(lamports <= available, LaunchError::RewardsPoolDepleted);
+
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &pool.key(),
+                &ctx.accounts.creator.key(),
+                lamports,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    pool.to_account_info(),
+                    ctx.accounts.creator.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                &[&[REWARDS_POOL_SEED, &[ctx.bumps.rewards_pool]]],
+            )?;
+        }
+
+        // Token rewards are paid out of tokens already in circulation, never newly minted, so
+        // they add no supply the curve reserves do not back
+        if tokens > 0 {
+            let pool_tokens = ctx
+                .accounts
+                .milestone_pool_token_account
+                .as_ref()
+                .ok_or(LaunchError::RewardsPoolDepleted)?;
+            require!(tokens <= pool_tokens.amount, LaunchError::RewardsPoolDepleted);
+
+            let launch_key = ctx.accounts.launch.key();
+            let pool_seeds: &[&[&[u8]]] =
+                &[&[MILESTONE_POOL_SEED, launch_key.as_ref(), &[ctx.bumps.milestone_pool]]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: pool_tokens.to_account_info(),
+                        to: ctx.accounts.creator_token_account.to_account_info(),
+                        authority: ctx.accounts.milestone_pool.to_account_info(),
+                    },
+                    pool_seeds,
+                ),
+                tokens,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn donate(ctx: Context<Donate>, lamports: u64, memo: Option<String>) -> Result<()> {
+        require!(lamports > 0, LaunchError::InvalidDonation);
+        if let Some(memo) = memo.as_ref() {
+            require!(memo.len() <= MAX_MEMO_LEN, LaunchError::MemoTooLong);
+        }
+
+        // A fresh community vault must be funded up to its rent-exempt minimum
+        let community_vault = &ctx.accounts.community_vault;
+        if community_vault.lamports() == 0 {
+            require!(
+                lamports >= Rent::get()?.minimum_balance(0),
+                LaunchError::InvalidDonation
+            );
+        }
+
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.donor.key(),
+            &community_vault.key(),
+            lamports,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.donor.to_account_info(),
+                community_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let launch = &mut ctx.accounts.launch;
+        launch.total_donations = launch.total_donations.saturating_add(lamports);
+
+        emit!(DonationEvent {
+            launch: launch.key(),
+            donor: ctx.accounts.donor.key(),
+            lamports,
+            memo,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn configure_raffle(ctx: Context<ConfigureRaffle>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps > 0 && fee_bps <= MAX_BPS, LaunchError::InvalidFeeBps);
+
+        let raffle = &mut ctx.accounts.raffle;
+        raffle.launch = ctx.accounts.launch.key();
+        raffle.fee_bps = fee_bps;
+        raffle.bump = ctx.bumps.raffle;
+
+        ctx.accounts.launch.raffle_enabled = true;
+        Ok(())
+    }
+
+    pub fn commit_raffle_draw(ctx: Context<CommitRaffleDraw>) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        require!(raffle.entry_count > 0, LaunchError::NoRaffleEntries);
+        require!(
+            raffle.randomness_account == Pubkey::default(),
+            LaunchError::RaffleDrawPending
+        );
+
+        // The randomness must be requested for the previous slot so nobody knows it at commit time
+        let clock = Clock::get()?;
+        let randomness = RandomnessAccountData::parse(ctx.accounts.randomness_account.data.borrow())
+            .map_err(|_| LaunchError::InvalidRandomness)?;
+        require!(
+            randomness.seed_slot == clock.slot.saturating_sub(1),
+            LaunchError::InvalidRandomness
+        );
+
+        raffle.randomness_account = ctx.accounts.randomness_account.key();
+        raffle.commit_slot = clock.slot;
+        Ok(())
+    }
+
+    pub fn draw_raffle(ctx: Context<DrawRaffle>) -> Result<()> {
+        let raffle = &mut ctx.accounts.raffle;
+        let clock = Clock::get()?;
+        let randomness = RandomnessAccountData::parse(ctx.accounts.randomness_account.data.borrow())
+            .map_err(|_| LaunchError::InvalidRandomness)?;
+        let value = randomness
+            .get_value(&clock)
+            .map_err(|_| LaunchError::RandomnessNotResolved)?;
+
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&value[..8]);
+        let index = (u64::from_le_bytes(seed) % raffle.entry_count as u64) as usize;
+        require_keys_eq!(
+            ctx.accounts.winner.key(),
+            raffle.entries[index],
+            LaunchError::InvalidRaffleWinner
+        );
+
+        // Pay out everything above the raffle account's rent-exempt minimum
+        let raffle_info = raffle.to_account_info();
+        let prize = raffle_info
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(Raffle::LEN));
+        **raffle_info.try_borrow_mut_lamports()? -= prize;
+        **ctx.accounts.winner.try_borrow_mut_lamports()? += prize;
+
+        raffle.entry_count = 0;
+        raffle.next_entry = 0;
+        raffle.randomness_account = Pubkey::default();
+
+        emit!(RaffleDrawnEvent {
+            launch: raffle.launch,
+            winner: ctx.accounts.winner.key(),
+            prize,
+            slot: clock.slot,
+        });
+        Ok(())
+    }
+
+    pub fn graduate_launch(ctx: Context<GraduateLaunch>) -> Result<GraduationResult> {
+        let launch = &mut ctx.accounts.launch;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+
+        // Losing the graduation race is not an error: later callers get the recorded outcome
+        if launch.state == LaunchState::Graduated {
+            return Ok(GraduationResult {
+                total_supply: launch.total_supply,
+                reserves: launch.sol_reserves,
+                final_price: launch.spot_price()?,
+                graduated_at: launch.graduated_at,
+                bounty: 0,
+            });
+        }
+
+        // Callers must bid at least the configured compute-unit price to take part in the race
+        let config = &ctx.accounts.config;
+        require!(
+            config.graduation_liquidity_bps == 0,
+            LaunchError::ChunkedGraduationRequired
+        );
+        if config.graduation_min_cu_price > 0 {
+            require!(
+                compute_unit_price(&ctx.accounts.instructions)? >= config.graduation_min_cu_price,
+                LaunchError::GraduationBidTooLow
+            );
+        }
+
+        // A launch that reached its own market-cap target closes its curve early
+        if launch.state == LaunchState::Trading && launch.graduation_target_reached()? {
+            launch.transition(LaunchState::CurveComplete)?;
+        }
+        require!(launch.state == LaunchState::CurveComplete, LaunchError::CurveNotComplete);
+        // Minting unsold supply needs the destination accounts only finalize_graduation takes
+        require!(
+            launch.residual_policy == ResidualPolicy::Burn
+                || launch.total_supply == launch.max_supply,
+            LaunchError::ChunkedGraduationRequired
+        );
+        launch.transition(LaunchState::Graduated)?;
+        let clock = Clock::get()?;
+        launch.graduated_at = clock.unix_timestamp;
+        launch.graduated_slot = clock.slot;
+
+        // The creator's bootstrap deposit was never raised funds, so it goes straight back
+        if launch.bootstrap_lamports > 0 {
+            let creator = ctx
+                .accounts
+                .creator
+                .as_ref()
+                .ok_or(LaunchError::InvalidBootstrapDeposit)?;
+            let launch_key = launch.key();
+            let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault.key(),
+                creator.key,
+                launch.bootstrap_lamports,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    creator.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                vault_seeds,
+            )?;
+            launch.bootstrap_lamports = 0;
+        }
+
+        // The first successful caller is paid the bounty out of curve reserves
+        let bounty = config.graduation_bounty.min(launch.sol_reserves);
+        if bounty > 0 {
+            let launch_key = launch.key();
+            let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault.key(),
+                &ctx.accounts.caller.key(),
+                bounty,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    ctx.accounts.caller.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                vault_seeds,
+            )?;
+            launch.sol_reserves -= bounty;
+        }
+
+        Ok(GraduationResult {
+            total_supply: launch.total_supply,
+            reserves: launch.sol_reserves,
+            final_price: launch.spot_price()?,
+            graduated_at: launch.graduated_at,
+            bounty,
+        })
+    }
+
+    // Graduations that seed DEX liquidity run in three steps so none of them has to fit the
+    // whole migration into one transaction. Step one closes the curve, pays the race bounty
+    // and fixes how much SOL and how many tokens go to the pool at the final price.
+    // Repeating it once graduation has started is a no-op.
+    pub fn begin_graduation(ctx: Context<BeginGraduation>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+        if matches!(launch.state, LaunchState::Graduating | LaunchState::Graduated) {
+            return Ok(());
+        }
+
+        let config = &ctx.accounts.config;
+        if config.graduation_min_cu_price > 0 {
+            require!(
+                compute_unit_price(&ctx.accounts.instructions)? >= config.graduation_min_cu_price,
+                LaunchError::GraduationBidTooLow
+            );
+        }
+        if launch.state == LaunchState::Trading && launch.graduation_target_reached()? {
+            launch.transition(LaunchState::CurveComplete)?;
+        }
+        require!(launch.state == LaunchState::CurveComplete, LaunchError::CurveNotComplete);
+        launch.transition(LaunchState::Graduating)?;
+
+        let bounty = config.graduation_bounty.min(launch.sol_reserves);
+        if bounty > 0 {
+            let launch_key = launch.key();
+            let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault.key(),
+                &ctx.accounts.caller.key(),
+                bounty,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    ctx.accounts.caller.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                vault_seeds,
+            )?;
+            launch.sol_reserves -= bounty;
+        }
+
+        // Pair the liquidity at the curve's final price so the pool opens where trading stopped
+        let mut lamports = bps_of(launch.sol_reserves, config.graduation_liquidity_bps)?;
+        let price = launch.spot_price()?.max(1);
+        let unit = launch.token_unit() as u128;
+        let mut tokens = lamports as u128 * unit / price as u128;
+
+        // AddToLiquidity residual goes in at the same price, backed by reserves left over after
+        // the standard deposit. Whatever those reserves cannot pair is never minted.
+        if launch.residual_policy == ResidualPolicy::AddToLiquidity && lamports > 0 {
+            let residual = (launch.max_supply - launch.total_supply) as u128;
+            let spare = (launch.sol_reserves - lamports) as u128;
+            let paired = residual.min(spare * unit / price as u128);
+            let paired_lamports = paired * price as u128 / unit;
+            let paired = paired_lamports * unit / price as u128;
+            lamports += paired_lamports as u64;
+            tokens += paired;
+            launch.residual_tokens = paired as u64;
+        }
+        launch.graduation_liquidity_lamports = lamports;
+        launch.graduation_liquidity_tokens =
+            tokens.try_into().map_err(|_| LaunchError::InvalidPriceCalculation)?;
+        launch.liquidity_deposited = 0;
+        Ok(())
+    }
+
+    // Step two, repeatable: move up to `max_lamports` of the pending liquidity from the vault
+    // into the launch's liquidity accounts as wSOL, minting the matching share of tokens
+    // alongside. Token amounts derive from the cumulative deposit, so chunk sizes do not
+    // change the total minted. Calls after everything is deposited do nothing.
+    pub fn deposit_liquidity(ctx: Context<DepositLiquidity>, max_lamports: u64) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.state == LaunchState::Graduating, LaunchError::GraduationNotStarted);
+        let target = launch.graduation_liquidity_lamports;
+        let chunk = target.saturating_sub(launch.liquidity_deposited).min(max_lamports);
+        if chunk == 0 {
+            return Ok(());
+        }
+
+        let launch_key = launch.key();
+        let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.liquidity_wsol.key(),
+            chunk,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.liquidity_wsol.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            vault_seeds,
+        )?;
+        token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::SyncNative {
+                account: ctx.accounts.liquidity_wsol.to_account_info(),
+            },
+        ))?;
+
+        let total_tokens = launch.graduation_liquidity_tokens;
+        let tokens_minted =
+            |deposited: u64| (total_tokens as u128 * deposited as u128 / target as u128) as u64;
+        let deposited = launch.liquidity_deposited + chunk;
+        let tokens = tokens_minted(deposited) - tokens_minted(launch.liquidity_deposited);
+        if tokens > 0 {
+            let mint_authority: &[&[&[u8]]] =
+                &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+            token::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::MintTo {
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.liquidity_token_account.to_account_info(),
+                        authority: ctx.accounts.mint_authority.to_account_info(),
+                    },
+                    mint_authority,
+                ),
+                tokens,
+            )?;
+        }
+        launch.sol_reserves -= chunk;
+        launch.liquidity_deposited = deposited;
+        Ok(())
+    }
+
+    // Step three: create the pool from the deposited liquidity through the configured DEX and
+    // mark the launch graduated. The pool instruction (`pool_data` plus the remaining
+    // accounts) is caller-built and signed by the liquidity authority, so only the admin may
+    // run it; it must consume both liquidity accounts, with LP locking left to the route.
+    // Once graduated, repeating this returns the recorded outcome.
+    pub fn finalize_graduation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeGraduation<'info>>,
+        pool_data: Vec<u8>,
+    ) -> Result<GraduationResult> {
+        let launch = &ctx.accounts.launch;
+        if launch.state == LaunchState::Graduated {
+            return Ok(GraduationResult {
+                total_supply: launch.total_supply,
+                reserves: launch.sol_reserves,
+                final_price: launch.spot_price()?,
+                graduated_at: launch.graduated_at,
+                bounty: 0,
+            });
+        }
+        require!(launch.state == LaunchState::Graduating, LaunchError::GraduationNotStarted);
+        require!(
+            launch.liquidity_deposited == launch.graduation_liquidity_lamports,
+            LaunchError::LiquidityNotDeposited
+        );
+
+        // Unsold supply follows the launch's residual policy before the pool is created.
+        // AddToLiquidity tokens were already deposited with matching SOL by begin_graduation.
+        let launch_key = launch.key();
+        let residual = launch.max_supply - launch.total_supply;
+        let destination = match launch.residual_policy {
+            ResidualPolicy::Burn | ResidualPolicy::AddToLiquidity => None,
+            ResidualPolicy::CommunityVault | ResidualPolicy::CreatorVesting => {
+                let account = ctx
+                    .accounts
+                    .residual_token_account
+                    .as_ref()
+                    .ok_or(LaunchError::InvalidResidualDestination)?;
+                let (expected_owner, _) = match launch.residual_policy {
+                    ResidualPolicy::CommunityVault => find_community_vault_address(&launch_key),
+                    _ => find_residual_vesting_address(&launch_key),
+                };
+                require_keys_eq!(
+                    account.owner,
+                    expected_owner,
+                    LaunchError::InvalidResidualDestination
+                );
+                Some(account.to_account_info())
+            }
+        };
+        let residual_minted = match destination {
+            Some(destination) if residual > 0 => {
+                let mint_authority: &[&[&[u8]]] =
+                    &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+                token::mint_to(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::MintTo {
+                            mint: ctx.accounts.mint.to_account_info(),
+                            to: destination,
+                            authority: ctx.accounts.mint_authority.to_account_info(),
+                        },
+                        mint_authority,
+                    ),
+                    residual,
+                )?;
+                residual
+            }
+            _ => launch.residual_tokens,
+        };
+
+        if launch.graduation_liquidity_lamports > 0 {
+            let authority_key = ctx.accounts.liquidity_authority.key();
+            let authority_seeds: &[&[&[u8]]] =
+                &[&[LIQUIDITY_SEED, launch_key.as_ref(), &[ctx.bumps.liquidity_authority]]];
+            let accounts = ctx
+                .remaining_accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.key(),
+                    is_signer: account.is_signer || account.key() == authority_key,
+                    is_writable: account.is_writable,
+                })
+                .collect();
+            invoke_signed(
+                &Instruction {
+                    program_id: ctx.accounts.dex_program.key(),
+                    accounts,
+                    data: pool_data,
+                },
+                ctx.remaining_accounts,
+                authority_seeds,
+            )?;
+
+            ctx.accounts.liquidity_wsol.reload()?;
+            ctx.accounts.liquidity_token_account.reload()?;
+            require!(
+                ctx.accounts.liquidity_wsol.amount == 0
+                    && ctx.accounts.liquidity_token_account.amount == 0,
+                LaunchError::LiquidityNotDeposited
+            );
+        }
+
+        let launch = &mut ctx.accounts.launch;
+        launch.transition(LaunchState::Graduated)?;
+        let clock = Clock::get()?;
+        launch.graduated_at = clock.unix_timestamp;
+        launch.graduated_slot = clock.slot;
+        launch.residual_tokens = residual_minted;
+
+        if launch.bootstrap_lamports > 0 {
+            let creator = ctx
+                .accounts
+                .creator
+                .as_ref()
+                .ok_or(LaunchError::InvalidBootstrapDeposit)?;
+            let launch_key = launch.key();
+            let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault.key(),
+                creator.key,
+                launch.bootstrap_lamports,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    creator.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                vault_seeds,
+            )?;
+            launch.bootstrap_lamports = 0;
+        }
+
+        Ok(GraduationResult {
+            total_supply: launch.total_supply,
+            reserves: launch.sol_reserves,
+            final_price: launch.spot_price()?,
+            graduated_at: launch.graduated_at,
+            bounty: 0,
+        })
+    }
+
+    // Release the creator's residual tokens linearly over the launch's vesting period,
+    // counted from graduation
+    pub fn claim_residual_vesting(ctx: Context<ClaimResidualVesting>) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.state == LaunchState::Graduated, LaunchError::NotGraduated);
+        require!(
+            launch.residual_policy == ResidualPolicy::CreatorVesting,
+            LaunchError::InvalidResidualPolicy
+        );
+
+        let elapsed = Clock::get()?
+            .unix_timestamp
+            .saturating_sub(launch.graduated_at)
+            .clamp(0, launch.residual_vesting_secs);
+        let vested = (launch.residual_tokens as u128 * elapsed as u128
+            / launch.residual_vesting_secs as u128) as u64;
+        let amount = vested.saturating_sub(launch.residual_claimed);
+        require!(amount > 0, LaunchError::NothingToClaim);
+        launch.residual_claimed = vested;
+
+        let launch_key = launch.key();
+        let vesting_seeds: &[&[&[u8]]] =
+            &[&[RESIDUAL_VESTING_SEED, launch_key.as_ref(), &[ctx.bumps.vesting_authority]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.vesting_escrow.to_account_info(),
+                    to: ctx.accounts.creator_token_account.to_account_info(),
+                    authority: ctx.accounts.vesting_authority.to_account_info(),
+                },
+                vesting_seeds,
+            ),
+            amount,
+        )?;
+        Ok(())
+    }
+
+    pub fn distribute_proceeds(ctx: Context<DistributeProceeds>) -> Result<()> {
+        ctx.accounts
+            .launch
+            .require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+
+        // Milestone and streaming launches only release what has unlocked so far
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let launch = &ctx.accounts.launch;
+        let mut release = launch.releasable(launch.sol_reserves, &clock)?;
+
+        // With a timelock, only an aged withdrawal request can be paid out, capped at its amount
+        let timelock = ctx.accounts.config.withdrawal_timelock_secs;
+        if timelock > 0 {
+            require!(
+                launch.withdrawal_requested_at != 0
+                    && now >= launch.withdrawal_requested_at.saturating_add(timelock),
+                LaunchError::WithdrawalTimelocked
+            );
+            release = release.min(launch.withdrawal_request_amount);
+        }
+        require!(release > 0, LaunchError::NothingToDistribute);
+        pay_out_proceeds(ctx.accounts, ctx.bumps.vault, release)?;
+
+        let launch_key = ctx.accounts.launch.key();
+        let launch = &mut ctx.accounts.launch;
+        launch.total_distributed = launch.total_distributed.saturating_add(release);
+        launch.sol_reserves -= release;
+        if timelock > 0 {
+            launch.withdrawal_requested_at = 0;
+            launch.withdrawal_request_amount = 0;
+
+            emit!(WithdrawalClaimedEvent {
+                launch: launch_key,
+                amount: release,
+                timestamp: now,
+            });
+        }
+        Ok(())
+    }
+
+    // Pay out whatever a streaming launch has unlocked so far. The stream already paces the
+    // creator, so the withdrawal-request timelock does not apply.
+    pub fn claim_stream(ctx: Context<DistributeProceeds>) -> Result<()> {
+        let launch = &ctx.accounts.launch;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+        require!(launch.stream_slots > 0, LaunchError::InvalidPayoutStream);
+        let release = launch.releasable(launch.sol_reserves, &Clock::get()?)?;
+        require!(release > 0, LaunchError::NothingToDistribute);
+        pay_out_proceeds(ctx.accounts, ctx.bumps.vault, release)?;
+
+        let launch = &mut ctx.accounts.launch;
+        launch.total_distributed = launch.total_distributed.saturating_add(release);
+        launch.sol_reserves -= release;
+        Ok(())
+    }
+
+    // Trades leave the treasury's fee share in the launch vault and count it here rather than
+    // writing to the treasury on every trade. Anyone may sweep the accrued amount to it.
+    pub fn claim_protocol_fees(ctx: Context<ClaimProtocolFees>) -> Result<()> {
+        let amount = ctx.accounts.launch.accrued_protocol_fees;
+        require!(amount > 0, LaunchError::NothingToDistribute);
+
+        // Launches opted into buybacks pool part of their fees for execute_buyback
+        let buyback = (amount as u128 * ctx.accounts.launch.buyback_bps as u128
+            / MAX_BPS as u128) as u64;
+        let launch_key = ctx.accounts.launch.key();
+        let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+        for (to, lamports) in [
+            (ctx.accounts.treasury.to_account_info(), amount - buyback),
+            (ctx.accounts.buyback_pool.to_account_info(), buyback),
+        ] {
+            if lamports == 0 {
+                continue;
+            }
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &ctx.accounts.vault.key(),
+                to.key,
+                lamports,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.vault.to_account_info(),
+                    to,
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                vault_seeds,
+            )?;
+        }
+
+        ctx.accounts.launch.accrued_protocol_fees = 0;
+        let config = &mut ctx.accounts.config;
+        config.protocol_fees_claimed = config.protocol_fees_claimed.saturating_add(amount);
+
+        emit!(ProtocolFeesClaimedEvent {
+            launch: launch_key,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    // Crank: swap the pooled buyback lamports for the protocol token through the configured
+    // DEX and burn everything bought. The swap route is caller-supplied (`swap_data` plus the
+    // remaining accounts), so only the admin may crank and `min_tokens_out` bounds the price.
+    pub fn execute_buyback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteBuyback<'info>>,
+        swap_data: Vec<u8>,
+        min_tokens_out: u64,
+    ) -> Result<()> {
+        let pool = ctx.accounts.buyback_pool.to_account_info();
+        let pool_seeds: &[&[&[u8]]] = &[&[BUYBACK_SEED, &[ctx.bumps.buyback_pool]]];
+        let lamports = pool.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(lamports > 0, LaunchError::NothingToDistribute);
+
+        // Wrap the pool's SOL so the DEX can take it as the swap input
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            pool.key,
+            &ctx.accounts.buyback_wsol.key(),
+            lamports,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                pool.clone(),
+                ctx.accounts.buyback_wsol.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            pool_seeds,
+        )?;
+        token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::SyncNative {
+                account: ctx.accounts.buyback_wsol.to_account_info(),
+            },
+        ))?;
+
+        let balance_before = ctx.accounts.buyback_token_account.amount;
+        let accounts = ctx
+            .remaining_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || account.key() == pool.key(),
+                is_writable: account.is_writable,
+            })
+            .collect();
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.dex_program.key(),
+                accounts,
+                data: swap_data,
+            },
+            ctx.remaining_accounts,
+            pool_seeds,
+        )?;
+
+        ctx.accounts.buyback_token_account.reload()?;
+        let bought = ctx.accounts.buyback_token_account.amount.saturating_sub(balance_before);
+        require!(bought >= min_tokens_out, LaunchError::SlippageExceeded);
+
+        // Burns the whole balance, so tokens stranded by an earlier failed burn go too
+        let burned = ctx.accounts.buyback_token_account.amount;
+        token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Burn {
+                    mint: ctx.accounts.protocol_token_mint.to_account_info(),
+                    from: ctx.accounts.buyback_token_account.to_account_info(),
+                    authority: pool,
+                },
+                pool_seeds,
+            ),
+            burned,
+        )?;
+
+        let config = &mut ctx.accounts.config;
+        config.protocol_tokens_burned = config.protocol_tokens_burned.saturating_add(burned);
+
+        emit!(BuybackBurnedEvent {
+            lamports,
+            tokens_burned: burned,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn request_creator_fee_withdrawal(ctx: Context<RequestCreatorFeeWithdrawal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+        let launch = &mut ctx.accounts.launch;
+        launch.require_authority(&ctx.accounts.authority.key(), OPERATOR_FEE_WITHDRAWAL)?;
+        launch.require_cosigner(ctx.accounts.secondary_authority.as_ref())?;
+        let amount = launch.releasable(launch.sol_reserves, &clock)?;
+        require!(amount > 0, LaunchError::NothingToDistribute);
+
+        launch.withdrawal_requested_at = now;
+        launch.withdrawal_request_amount = amount;
+
+        emit!(WithdrawalRequestedEvent {
+            launch: launch.key(),
+            amount,
+            available_at: now.saturating_add(ctx.accounts.config.withdrawal_timelock_secs),
+        });
+        Ok(())
+    }
+
+    pub fn sweep<'info>(ctx: Context<'_, '_, 'info, 'info, Sweep<'info>>) -> Result<()> {
+        let launch_key = ctx.accounts.launch.key();
+        let mint_authority_key = ctx.accounts.mint_authority.key();
+        let mint_authority_seeds: &[&[&[u8]]] =
+            &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[ctx.bumps.mint_authority]]];
+
+        // Close empty token accounts owned by the launch PDA, reclaiming rent into it
+        for info in ctx.remaining_accounts.iter() {
+            let token_account = Account::<TokenAccount>::try_from(info)?;
+            require_keys_eq!(token_account.owner, mint_authority_key, LaunchError::InvalidSweepAccount);
+            require!(token_account.amount == 0, LaunchError::InvalidSweepAccount);
+
+            token::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::CloseAccount {
+                    account: info.clone(),
+                    destination: ctx.accounts.mint_authority.to_account_info(),
+                    authority: ctx.accounts.mint_authority.to_account_info(),
+                },
+                mint_authority_seeds,
+            ))?;
+        }
+
+        // Forward everything held by the PDA to the treasury, minus the cranker's bounty
+        let swept = ctx.accounts.mint_authority.lamports();
+        require!(swept > 0, LaunchError::NothingToSweep);
+        let bounty = bps_of(swept, ctx.accounts.config.sweep_bounty_bps)?;
+
+        for (recipient, lamports) in [
+            (ctx.accounts.cranker.to_account_info(), bounty),
+            (ctx.accounts.treasury.to_account_info(), swept - bounty),
+        ] {
+            if lamports == 0 {
+                continue;
+            }
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                &mint_authority_key,
+                recipient.key,
+                lamports,
+            );
+            invoke_signed(
+                &transfer_ix,
+                &[
+                    ctx.accounts.mint_authority.to_account_info(),
+                    recipient,
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+                mint_authority_seeds,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn commit_snapshot(
+        ctx: Context<CommitSnapshot>,
+        merkle_root: [u8; 32],
+        slot: u64,
+    ) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            authority == launch.creator || authority == ctx.accounts.config.admin,
+            LaunchError::Unauthorized
+        );
+        require!(slot <= Clock::get()?.slot, LaunchError::InvalidSnapshotSlot);
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.launch = launch.key();
+        snapshot.index = launch.snapshot_count;
+        snapshot.merkle_root = merkle_root;
+        snapshot.slot = slot;
+        snapshot.committed_by = authority;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        launch.snapshot_count = launch
+            .snapshot_count
+            .checked_add(1)
+            .ok_or(LaunchError::InvalidSnapshotSlot)?;
+        Ok(())
+    }
+
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: ProposalAction) -> Result<()> {
+        let launch = &mut ctx.accounts.launch;
+        require!(launch.state == LaunchState::Graduated, LaunchError::NotGraduated);
+        require!(
+            ctx.accounts.proposer_token_account.amount > 0,
+            LaunchError::NotATokenHolder
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.launch = launch.key();
+        proposal.index = launch.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action;
+        proposal.yes_votes = 0;
+        proposal.no_votes = 0;
+        proposal.voting_ends_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(PROPOSAL_VOTING_SECS);
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        launch.proposal_count = launch
+            .proposal_count
+            .checked_add(1)
+            .ok_or(LaunchError::InvalidProposal)?;
+        Ok(())
+    }
+
+    pub fn vote(ctx: Context<Vote>, amount: u64, support: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            Clock::get()?.unix_timestamp < proposal.voting_ends_at,
+            LaunchError::VotingClosed
+        );
+        require!(amount > 0, LaunchError::InvalidVoteAmount);
+
+        // Escrow the voting tokens with the proposal until voting ends
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.voter_token_account.to_account_info(),
+                    to: ctx.accounts.escrow_token_account.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        if support {
+            proposal.yes_votes = proposal.yes_votes.saturating_add(amount);
+        } else {
+            proposal.no_votes = proposal.no_votes.saturating_add(amount);
+        }
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.proposal = proposal.key();
+        vote_record.voter = ctx.accounts.voter.key();
+        vote_record.amount = amount;
+        vote_record.support = support;
+        vote_record.bump = ctx.bumps.vote_record;
+        Ok(())
+    }
+
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let launch = &mut ctx.accounts.launch;
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.voting_ends_at,
+            LaunchError::VotingOpen
+        );
+        require!(!proposal.executed, LaunchError::ProposalAlreadyExecuted);
+
+        let quorum = bps_of(launch.total_supply, PROPOSAL_QUORUM_BPS)?;
+        require!(
+            proposal.yes_votes > proposal.no_votes && proposal.yes_votes >= quorum,
+            LaunchError::ProposalRejected
+        );
+
+        match proposal.action {
+            ProposalAction::SetFeeDestination { destination } => {
+                launch.fee_destination = destination;
+            }
+            ProposalAction::ApproveMetadataChange { content_hash } => {
+                launch.approved_metadata_hash = content_hash;
+            }
+        }
+
+        proposal.executed = true;
+        Ok(())
+    }
+
+    pub fn withdraw_vote(ctx: Context<WithdrawVote>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            Clock::get()?.unix_timestamp >= proposal.voting_ends_at,
+            LaunchError::VotingOpen
+        );
+
+        // Return the escrowed tokens; the vote record is closed back to the voter
+        let launch_key = proposal.launch;
+        let index = proposal.index.to_le_bytes();
+        let proposal_seeds: &[&[&[u8]]] =
+            &[&[PROPOSAL_SEED, launch_key.as_ref(), &index, &[proposal.bump]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.voter_token_account.to_account_info(),
+                    authority: proposal.to_account_info(),
+                },
+                proposal_seeds,
+            ),
+            ctx.accounts.vote_record.amount,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        expires_at: i64,
+        spend_cap: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, LaunchError::InvalidSessionExpiry);
+        require!(spend_cap > 0, LaunchError::InvalidSpendCap);
+
+        let session = &mut ctx.accounts.session;
+        session.owner = ctx.accounts.owner.key();
+        session.session_key = session_key;
+        session.expires_at = expires_at;
+        session.spend_cap = spend_cap;
+        session.spent = 0;
+        session.bump = ctx.bumps.session;
+
+        // Escrow the spend cap so the session key never needs owner funds. The escrow also
+        // holds its own rent minimum, so buys can draw the full cap without closing it.
+        let escrow_lamports = spend_cap
+            .checked_add(Rent::get()?.minimum_balance(0))
+            .ok_or(LaunchError::InvalidSpendCap)?;
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.owner.key(),
+            &ctx.accounts.session_escrow.key(),
+            escrow_lamports,
+        );
+        invoke(
+            &transfer_ix,
+            &[
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.session_escrow.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        // Session rent is returned to the owner by the close constraint; the escrow is
+        // emptied here
+        let session_key = ctx.accounts.session.key();
+        let escrow_seeds: &[&[&[u8]]] =
+            &[&[SESSION_ESCROW_SEED, session_key.as_ref(), &[ctx.bumps.session_escrow]]];
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.session_escrow.key(),
+            &ctx.accounts.owner.key(),
+            ctx.accounts.session_escrow.lamports(),
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.session_escrow.to_account_info(),
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            escrow_seeds,
+        )?;
+        Ok(())
+    }
+
+    // Buy on the session owner's behalf, paid out of the session escrow. The buy runs through
+    // the same core as direct trades, with the owner as the buyer; only the funding differs.
+    pub fn buy_tokens_with_session(
+        ctx: Context<BuyTokensWithSession>,
+        amount: u64,
+    ) -> Result<TradeResult> {
+        require!(
+            Clock::get()?.unix_timestamp < ctx.accounts.session.expires_at,
+            LaunchError::SessionExpired
+        );
+
+        let session_key = ctx.accounts.session.key();
+        let escrow_seeds: &[&[&[u8]]] =
+            &[&[SESSION_ESCROW_SEED, session_key.as_ref(), &[ctx.bumps.session_escrow]]];
+        let accounts = &mut *ctx.accounts;
+        let result = execute_buy(
+            BuyAccounts {
+                program_id: ctx.program_id,
+                launch: &mut accounts.launch,
+                buyer: accounts.owner.to_account_info(),
+                payer: accounts.session_escrow.to_account_info(),
+                payer_seeds: escrow_seeds,
+                blacklist_entry: &accounts.blacklist_entry,
+                market_maker_entry: None,
+                vault: &accounts.vault,
+                stats: &mut accounts.stats,
+                config: &accounts.config,
+                frontend_fee_recipient: accounts.frontend_fee_recipient.as_ref(),
+                mint: &accounts.mint,
+                mint_authority: &accounts.mint_authority,
+                mint_authority_bump: ctx.bumps.mint_authority,
+                token_account: &accounts.owner_token_account,
+                rent_payer: &accounts.rent_payer,
+                attestation_token_account: accounts.attestation_token_account.as_deref(),
+                gate_token_account: accounts.gate_token_account.as_deref(),
+                raffle: accounts.raffle.as_mut(),
+                instructions: accounts.instructions.as_ref(),
+                hook_program: accounts.hook_program.as_ref(),
+                hook_allowlist_entry: accounts.hook_allowlist_entry.as_deref(),
+                trade_tree: accounts.trade_tree.as_ref(),
+                tree_authority: accounts.tree_authority.as_ref(),
+                tree_authority_bump: ctx.bumps.tree_authority,
+                compression_program: accounts.compression_program.as_ref(),
+                noop_program: accounts.noop_program.as_ref(),
+                memo_program: None,
+                trade_receipt: None,
+                buyer_state: Some((&mut accounts.owner_buyer_state, ctx.bumps.owner_buyer_state)),
+                system_program: &accounts.system_program,
+                token_program: &accounts.token_program,
+                associated_token_program: &accounts.associated_token_program,
+            },
+            ctx.remaining_accounts,
+            amount,
+            u64::MAX,
+            None,
+        )?;
+
+        // The escrow cannot pay more than the cap, but the cap is a commitment of its own
+        let session = &mut ctx.accounts.session;
+        let spent = session
+            .spent
+            .checked_add(result.lamports)
+            .ok_or(LaunchError::SessionSpendCapExceeded)?;
+        require!(spent <= session.spend_cap, LaunchError::SessionSpendCapExceeded);
+        session.spent = spent;
+        Ok(result)
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLaunchFeeOverride<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLaunchReported<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProveFreshness<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: SlotHashes sysvar, parsed in the handler
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFreshTxMode<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetColdWallet<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+
+    #[account(seeds = [TREASURY_SEED], bump)]
+    pub treasury_vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized,
+        has_one = cold_wallet @ LaunchError::AccountMismatch
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury_vault: SystemAccount<'info>,
+
+    /// CHECK: Registered cold wallet, validated against the config
+    #[account(mut)]
+    pub cold_wallet: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TopUpLaunchVault<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [TREASURY_SEED], bump)]
+    pub treasury_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        constraint = config.pending_admin == pending_admin.key() @ LaunchError::NotPendingAdmin
+    )]
+    pub config: Account<'info, Config>,
+
+    pub pending_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(hook_program: Pubkey)]
+pub struct AllowHookProgram<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = HookAllowlistEntry::LEN,
+        seeds = [HOOK_ALLOWLIST_SEED, hook_program.as_ref()],
+        bump
+    )]
+    pub hook_allowlist_entry: Account<'info, HookAllowlistEntry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DisallowHookProgram<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [HOOK_ALLOWLIST_SEED, hook_allowlist_entry.hook_program.as_ref()],
+        bump = hook_allowlist_entry.bump,
+        close = admin
+    )]
+    pub hook_allowlist_entry: Account<'info, HookAllowlistEntry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitTradeTree<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+
+    /// CHECK: Pre-allocated tree account, initialized by the compression program
+    #[account(mut)]
+    pub trade_tree: UncheckedAccount<'info>,
+
+    /// CHECK: PDA authority over the launch's trade tree
+    #[account(seeds = [TREE_AUTHORITY_SEED, launch.key().as_ref()], bump)]
+    pub tree_authority: UncheckedAccount<'info>,
+
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub noop_program: Program<'info, Noop>,
+}
+
+#[derive(Accounts)]
+pub struct MintDeed<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = 0,
+        mint::authority = mint_authority,
+        seeds = [DEED_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub deed_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = deed_mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_deed_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct AssumeCreator<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    pub holder: Signer<'info>,
+
+    #[account(
+        constraint = holder_deed_account.mint == launch.deed_mint
+            && launch.deed_mint != Pubkey::default()
+            && holder_deed_account.owner == holder.key()
+            && holder_deed_account.amount == 1 @ LaunchError::NotDeedHolder
+    )]
+    pub holder_deed_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetHookProgram<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+
+    // Required unless the hook is being removed
+    pub hook_allowlist_entry: Option<Account<'info, HookAllowlistEntry>>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToBlacklist<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = BlacklistEntry::LEN,
+        seeds = [BLACKLIST_SEED, wallet.as_ref()],
+        bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromBlacklist<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [BLACKLIST_SEED, blacklist_entry.wallet.as_ref()],
+        bump = blacklist_entry.bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddMarketMaker<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = MarketMakerEntry::LEN,
+        seeds = [MARKET_MAKER_SEED, wallet.as_ref()],
+        bump
+    )]
+    pub market_maker_entry: Account<'info, MarketMakerEntry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMarketMaker<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [MARKET_MAKER_SEED, market_maker_entry.wallet.as_ref()],
+        bump = market_maker_entry.bump
+    )]
+    pub market_maker_entry: Account<'info, MarketMakerEntry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRewardSchedule<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = RewardSchedule::LEN,
+        seeds = [REWARD_SCHEDULE_SEED],
+        bump
+    )]
+    pub reward_schedule: Account<'info, RewardSchedule>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimMilestoneReward<'info> {
+    #[account(has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [STATS_SEED, launch.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, LaunchStats>,
+
+    #[account(seeds = [REWARD_SCHEDULE_SEED], bump = reward_schedule.bump)]
+    pub reward_schedule: Account<'info, RewardSchedule>,
+
+    // Protocol-wide community pool, funded by the treasury
+    #[account(
+        mut,
+        seeds = [REWARDS_POOL_SEED],
+        bump
+    )]
+    pub rewards_pool: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA owning the launch's milestone reward tokens
+    #[account(seeds = [MILESTONE_POOL_SEED, launch.key().as_ref()], bump)]
+    pub milestone_pool: UncheckedAccount<'info>,
+
+    // Required when a claimed tier pays tokens
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = milestone_pool,
+    )]
+    pub milestone_pool_token_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLaunch<'info> {
+    #[account(init, payer = payer, space = Launch::LEN)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    /// CHECK: Launch authority, which may be a multisig vault that cannot sign here
+    pub creator: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = TOKEN_DECIMALS,
+        mint::authority = mint_authority,
+    )]
+    pub mint: Account<'info, Mint>,
+    
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    // Tolerates an ATA someone created ahead of the launch transaction
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = LaunchStats::LEN,
+        seeds = [STATS_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub stats: Account<'info, LaunchStats>,
+
+    /// CHECK: Metaplex metadata PDA for the mint, created in the handler
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump
+    )]
+    pub metadata: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateLaunchParams<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct GetCollateralValue<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(seeds = [STATS_SEED, launch.key().as_ref()], bump = stats.bump)]
+    pub stats: Account<'info, LaunchStats>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLaunchBatch<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Launch authority shared by every launch in the batch
+    pub creator: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+pub struct Relaunch<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(has_one = creator @ LaunchError::Unauthorized)]
+    pub original_launch: Account<'info, Launch>,
+
+    // Holder snapshot of the original launch to airdrop against
+    #[account(constraint = original_snapshot.launch == original_launch.key() @ LaunchError::InvalidRelaunch)]
+    pub original_snapshot: Option<Account<'info, Snapshot>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = mint_authority,
+    )]
+    pub airdrop_escrow: Option<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct OpenCompetition<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Competition::LEN,
+        seeds = [COMPETITION_SEED, &(config.competition_epoch + 1).to_le_bytes()],
+        bump
+    )]
+    pub competition: Account<'info, Competition>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCompetition<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub competition: Account<'info, Competition>,
+
+    // Funded with plain transfers by the protocol or sponsors before closing
+    #[account(seeds = [COMPETITION_VAULT_SEED, competition.key().as_ref()], bump)]
+    pub competition_vault: SystemAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCompetitionPrize<'info> {
+    #[account(mut)]
+    pub competition: Account<'info, Competition>,
+
+    #[account(mut, seeds = [COMPETITION_VAULT_SEED, competition.key().as_ref()], bump)]
+    pub competition_vault: SystemAccount<'info>,
+
+    #[account(
+        init,
+        payer = winner,
+        space = CompetitionClaim::LEN,
+        seeds = [COMPETITION_CLAIM_SEED, competition.key().as_ref(), winner.key().as_ref()],
+        bump
+    )]
+    pub prize_claim: Account<'info, CompetitionClaim>,
+
+    #[account(mut)]
+    pub winner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAirdrop<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init,
+        payer = claimant,
+        space = AirdropClaim::LEN,
+        seeds = [AIRDROP_CLAIM_SEED, launch.key().as_ref(), claimant.key().as_ref()],
+        bump
+    )]
+    pub airdrop_claim: Account<'info, AirdropClaim>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority, owner of the airdrop escrow
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = mint_authority,
+    )]
+    pub airdrop_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        associated_token::mint = mint,
+        associated_token::authority = claimant,
+    )]
+    pub claimant_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimUnclaimed<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+
+    // Writable because burning changes supply
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority, owner of the airdrop escrow
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = mint_authority,
+    )]
+    pub airdrop_escrow: Account<'info, TokenAccount>,
+
+    // Required unless the remainder is burned
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Trade<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+    
+    #[account(mut)]
+    pub trader: Signer<'info>,
+
+    /// CHECK: Blacklist PDA for the trader, which must not exist
+    #[account(seeds = [BLACKLIST_SEED, trader.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+
+    // Allow-list entry for market-maker traders, relaxing anti-bot limits after the launch window
+    #[account(
+        seeds = [MARKET_MAKER_SEED, trader.key().as_ref()],
+        bump = market_maker_entry.bump
+    )]
+    pub market_maker_entry: Option<Account<'info, MarketMakerEntry>>,
+    
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [STATS_SEED, launch.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, LaunchStats>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Integrator frontend that routed the trade, paid a share of the fee
+    #[account(mut)]
+    pub frontend_fee_recipient: Option<UncheckedAccount<'info>>,
+    
+    // Writable because minting and burning change supply
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+    
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: Trader's ATA, created idempotently on buys and validated in the handler
+    #[account(mut)]
+    pub trader_token_account: UncheckedAccount<'info>,
+
+    // Required for sells paid out in wSOL
+    #[account(
+        mut,
+        constraint = trader_wsol_account.mint == token::spl_token::native_mint::ID
+            && trader_wsol_account.owner == trader.key() @ LaunchError::WsolAccountRequired
+    )]
+    pub trader_wsol_account: Option<Account<'info, TokenAccount>>,
+
+    // Pays ATA rent on a first buy, so sponsors can cover it for the trader
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+
+    // Required when the config sets an attestation mint
+    pub attestation_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the launch gates buys on holding another mint
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the launch runs a raffle
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED, launch.key().as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Option<Account<'info, Raffle>>,
+
+    // Required when the launch only accepts freshly assembled transactions
+    /// CHECK: Instructions sysvar, scanned for a prove_freshness instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Required when the launch has a trade hook
+    /// CHECK: Hook program, validated against the launch and the allow-list
+    #[account(executable, address = launch.hook_program @ LaunchError::HookNotAllowed)]
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [HOOK_ALLOWLIST_SEED, launch.hook_program.as_ref()],
+        bump = hook_allowlist_entry.bump
+    )]
+    pub hook_allowlist_entry: Option<Account<'info, HookAllowlistEntry>>,
+
+    // Required when the launch records trades in a compressed tree
+    /// CHECK: Concurrent merkle tree, validated against the launch
+    #[account(mut, address = launch.trade_tree @ LaunchError::InvalidTradeTree)]
+    pub trade_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PDA authority over the launch's trade tree
+    #[account(seeds = [TREE_AUTHORITY_SEED, launch.key().as_ref()], bump)]
+    pub tree_authority: Option<UncheckedAccount<'info>>,
+
+    pub compression_program: Option<Program<'info, SplAccountCompression>>,
+    pub noop_program: Option<Program<'info, Noop>>,
+
+    // Required on buys that carry a memo
+    /// CHECK: SPL Memo program, checked by address
+    #[account(address = MEMO_PROGRAM_ID)]
+    pub memo_program: Option<UncheckedAccount<'info>>,
+
+    // Passed on buys that want a proof-of-purchase receipt
+    #[account(
+        init,
+        payer = trader,
+        space = TradeReceipt::LEN,
+        seeds = [RECEIPT_SEED, launch.key().as_ref(), &launch.trade_seq.to_le_bytes()],
+        bump
+    )]
+    pub trade_receipt: Option<Account<'info, TradeReceipt>>,
+
+    // Passed by holders accruing loyalty points
+    #[account(
+        init_if_needed,
+        payer = rent_payer,
+        space = BuyerState::LEN,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), trader.key().as_ref()],
+        bump
+    )]
+    pub buyer_state: Option<Account<'info, BuyerState>>,
+
+    /// CHECK: The trader's BuyerState address. Once that account exists buyer_state must be
+    /// passed, so its spend cap and cost basis cannot be skipped by leaving it out
+    #[account(seeds = [BUYER_STATE_SEED, launch.key().as_ref(), trader.key().as_ref()], bump)]
+    pub buyer_state_address: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct SellForMany<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    // Delegate approved on every token account being sold
+    pub delegate: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [STATS_SEED, launch.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, LaunchStats>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    // Required when the launch only accepts freshly assembled transactions
+    /// CHECK: Instructions sysvar, scanned for a prove_freshness instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Required when the launch has a trade hook, which is called once per sell
+    /// CHECK: Hook program, validated against the launch and the allow-list
+    #[account(executable, address = launch.hook_program @ LaunchError::HookNotAllowed)]
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [HOOK_ALLOWLIST_SEED, launch.hook_program.as_ref()],
+        bump = hook_allowlist_entry.bump
+    )]
+    pub hook_allowlist_entry: Option<Account<'info, HookAllowlistEntry>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTradeReceipt<'info> {
+    #[account(
+        mut,
+        seeds = [RECEIPT_SEED, trade_receipt.launch.as_ref(), &trade_receipt.seq.to_le_bytes()],
+        bump = trade_receipt.bump,
+        has_one = buyer @ LaunchError::Unauthorized,
+        close = buyer
+    )]
+    pub trade_receipt: Account<'info, TradeReceipt>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateOtcOffer<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = OtcOffer::LEN,
+        seeds = [OTC_OFFER_SEED, launch.key().as_ref(), seller.key().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, OtcOffer>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOtcOffer<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [OTC_OFFER_SEED, launch.key().as_ref(), seller.key().as_ref()],
+        bump = offer.bump,
+        has_one = seller @ LaunchError::Unauthorized,
+        close = seller
+    )]
+    pub offer: Account<'info, OtcOffer>,
+
+    /// CHECK: Offer creator receiving the ask, validated against the offer
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Blacklist PDA for the buyer, which must not exist
+    #[account(seeds = [BLACKLIST_SEED, buyer.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = treasury @ LaunchError::AccountMismatch
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Protocol treasury to receive the settlement fee, validated against the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOtcOffer<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [OTC_OFFER_SEED, launch.key().as_ref(), seller.key().as_ref()],
+        bump = offer.bump,
+        has_one = seller @ LaunchError::Unauthorized,
+        close = seller
+    )]
+    pub offer: Account<'info, OtcOffer>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = offer,
+    )]
+    pub escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+    )]
+    pub seller_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetMilestones<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCrowdfund<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pledge<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BuyerState::LEN,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Required when the config sets an attestation mint
+    pub attestation_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the launch gates buys on holding another mint
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPledge<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = launch @ LaunchError::AccountMismatch,
+        has_one = buyer @ LaunchError::Unauthorized,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_state.bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeAllocation<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFairMint<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = launch @ LaunchError::AccountMismatch,
+        has_one = buyer @ LaunchError::Unauthorized,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_state.bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct RefundPledge<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = launch @ LaunchError::AccountMismatch,
+        has_one = buyer @ LaunchError::Unauthorized,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_state.bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelLaunch<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Launch creator or protocol admin
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    // Closed to the buyer by claim_refund once its tracked tokens are all refunded
+    #[account(
+        mut,
+        has_one = launch @ LaunchError::AccountMismatch,
+        has_one = buyer @ LaunchError::Unauthorized,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_state.bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = mint, token::authority = buyer)]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FundLoyaltyRewards<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [LOYALTY_POOL_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub loyalty_pool: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLoyaltyReward<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_state.bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        mut,
+        seeds = [LOYALTY_POOL_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub loyalty_pool: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureEarlySupporters<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSpendCap<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BuyerState::LEN,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundLpRewards<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [LP_REWARD_POOL_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub lp_reward_pool: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimLpRewards<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_state.bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        mut,
+        seeds = [LP_REWARD_POOL_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub lp_reward_pool: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimEarlySupporterBadge<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump = buyer_state.bump
+    )]
+    pub buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = EarlySupporterBadge::LEN,
+        seeds = [EARLY_SUPPORTER_SEED, launch.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub badge: Account<'info, EarlySupporterBadge>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureMaxWallet<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCurveStages<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFlatSale<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureGraduationVenue<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRebate<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RetuneCurve<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        seeds = [STATS_SEED, launch.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, LaunchStats>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCurveType<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositBootstrapLiquidity<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCircuitBreaker<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    // Launch creator or an operator allowed to manage the breaker
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureReservations<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Reserve<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Reservation::LEN,
+        seeds = [RESERVATION_SEED, launch.key().as_ref(), &launch.reservation_count.to_le_bytes()],
+        bump
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleReservations<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFeeSplit<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = FeeSplit::LEN,
+        seeds = [FEE_SPLIT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub fee_split: Account<'info, FeeSplit>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    // Required on high-security launches
+    pub secondary_authority: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFeeSplit<'info> {
+    #[account(mut, seeds = [FEE_SPLIT_SEED, fee_split.launch.as_ref()], bump = fee_split.bump)]
+    pub fee_split: Account<'info, FeeSplit>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreatorIdentity<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    pub creator: Signer<'info>,
+
+    /// CHECK: name record or profile PDA; ownership is verified against `kind` in the handler
+    pub identity: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadataUri<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    // Launch creator or an operator allowed to manage metadata
+    pub authority: Signer<'info>,
+
+    // Required on high-security launches
+    pub secondary_authority: Option<Signer<'info>>,
+
+    /// CHECK: PDA for mint authority, the metadata's update authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex metadata PDA for the launch mint
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), launch.mint.as_ref()],
+        seeds::program = token_metadata_program.key(),
+        bump
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+pub struct CommitTokenomics<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = TokenomicsRevision::LEN,
+        seeds = [TOKENOMICS_SEED, launch.key().as_ref(), &launch.tokenomics_version.to_le_bytes()],
+        bump
+    )]
+    pub revision: Account<'info, TokenomicsRevision>,
+
+    // Launch creator or an operator allowed to manage metadata
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    // Required on high-security launches
+    pub secondary_authority: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSecondaryAuthority<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+
+    // The current secondary authority, if one is set
+    pub secondary_authority: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct Donate<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [COMMUNITY_VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub community_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub donor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureRaffle<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = Raffle::LEN,
+        seeds = [RAFFLE_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CommitRaffleDraw<'info> {
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED, raffle.launch.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    /// CHECK: Switchboard randomness account, parsed and validated in the handler
+    #[account(owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID @ LaunchError::InvalidRandomness)]
+    pub randomness_account: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DrawRaffle<'info> {
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED, raffle.launch.as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    /// CHECK: Switchboard randomness account committed for this draw
+    #[account(
+        address = raffle.randomness_account,
+        owner = SWITCHBOARD_ON_DEMAND_PROGRAM_ID @ LaunchError::InvalidRandomness
+    )]
+    pub randomness_account: UncheckedAccount<'info>,
+
+    /// CHECK: Winning entry, validated against the drawn index
+    #[account(mut)]
+    pub winner: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GraduateLaunch<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    // Required on high-security launches
+    pub secondary_authority: Option<Signer<'info>>,
+
+    /// CHECK: Launch creator, refunded any bootstrap deposit; required when one was made
+    #[account(mut, address = launch.creator @ LaunchError::AccountMismatch)]
+    pub creator: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Instructions sysvar, read to verify the caller's compute-unit price
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BeginGraduation<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    // Required on high-security launches
+    pub secondary_authority: Option<Signer<'info>>,
+
+    /// CHECK: Instructions sysvar, read to verify the caller's compute-unit price
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositLiquidity<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: PDA owning the launch's graduation liquidity
+    #[account(seeds = [LIQUIDITY_SEED, launch.key().as_ref()], bump)]
+    pub liquidity_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = token::spl_token::native_mint::ID,
+        token::authority = liquidity_authority
+    )]
+    pub liquidity_wsol: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = mint, token::authority = liquidity_authority)]
+    pub liquidity_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeGraduation<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: PDA owning the launch's graduation liquidity
+    #[account(seeds = [LIQUIDITY_SEED, launch.key().as_ref()], bump)]
+    pub liquidity_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = token::spl_token::native_mint::ID,
+        token::authority = liquidity_authority
+    )]
+    pub liquidity_wsol: Account<'info, TokenAccount>,
+
+    #[account(mut, token::mint = launch.mint, token::authority = liquidity_authority)]
+    pub liquidity_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: DEX program creating the pool, validated against the config
+    #[account(
+        executable,
+        address = config.graduation_dex(launch.graduation_venue) @ LaunchError::AccountMismatch
+    )]
+    pub dex_program: UncheckedAccount<'info>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    // Receives unsold supply under the CommunityVault and CreatorVesting policies; its owner
+    // is checked against the policy's PDA
+    #[account(mut, token::mint = launch.mint)]
+    pub residual_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Launch creator, refunded any bootstrap deposit; required when one was made
+    #[account(mut, address = launch.creator @ LaunchError::AccountMismatch)]
+    pub creator: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimResidualVesting<'info> {
+    #[account(mut, has_one = creator @ LaunchError::Unauthorized)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA owning the creator's residual vesting escrow
+    #[account(seeds = [RESIDUAL_VESTING_SEED, launch.key().as_ref()], bump)]
+    pub vesting_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vesting_authority,
+    )]
+    pub vesting_escrow: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeProceeds<'info> {
+    #[account(mut, has_one = fee_destination @ LaunchError::AccountMismatch)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = treasury @ LaunchError::AccountMismatch
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Creator fee destination to receive SOL, validated against the launch
+    #[account(mut)]
+    pub fee_destination: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury to receive SOL, validated against the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    // Required on high-security launches
+    pub secondary_authority: Option<Signer<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimProtocolFees<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = treasury @ LaunchError::AccountMismatch
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: Protocol treasury to receive SOL, validated against the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    // Receives the launch's buyback share, swapped and burned by execute_buyback
+    #[account(mut, seeds = [BUYBACK_SEED], bump)]
+    pub buyback_pool: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteBuyback<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ LaunchError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+
+    #[account(mut, seeds = [BUYBACK_SEED], bump)]
+    pub buyback_pool: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = token::spl_token::native_mint::ID,
+        token::authority = buyback_pool
+    )]
+    pub buyback_wsol: Account<'info, TokenAccount>,
+
+    #[account(mut, address = config.protocol_token_mint)]
+    pub protocol_token_mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = protocol_token_mint, token::authority = buyback_pool)]
+    pub buyback_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Swap program, validated against the config
+    #[account(
+        executable,
+        address = config.buyback_dex_program @ LaunchError::AccountMismatch
+    )]
+    pub dex_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct RequestCreatorFeeWithdrawal<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    // Launch creator or an operator allowed to request withdrawals
+    pub authority: Signer<'info>,
+
+    // Required on high-security launches
+    pub secondary_authority: Option<Signer<'info>>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct Sweep<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = treasury @ LaunchError::AccountMismatch
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: PDA for mint authority, owner of protocol token accounts
+    #[account(
+        mut,
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury to receive SOL, validated against the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CommitSnapshot<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Snapshot::LEN,
+        seeds = [SNAPSHOT_SEED, launch.key().as_ref(), &launch.snapshot_count.to_le_bytes()],
+        bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    // Launch creator or protocol admin
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::LEN,
+        seeds = [PROPOSAL_SEED, launch.key().as_ref(), &launch.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        token::mint = launch.mint,
+        token::authority = proposer,
+    )]
+    pub proposer_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Vote<'info> {
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut, has_one = launch @ LaunchError::AccountMismatch)]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = VoteRecord::LEN,
+        seeds = [VOTE_SEED, proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = voter,
+    )]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        associated_token::mint = mint,
+        associated_token::authority = proposal,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut, has_one = launch @ LaunchError::AccountMismatch)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVote<'info> {
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        close = voter,
+        has_one = proposal @ LaunchError::AccountMismatch,
+        has_one = voter @ LaunchError::Unauthorized,
+        seeds = [VOTE_SEED, proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    #[account(mut, token::authority = voter)]
+    pub voter_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = voter_token_account.mint,
+        associated_token::authority = proposal,
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = SessionAuthority::LEN,
+        seeds = [SESSION_SEED, owner.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionAuthority>,
+
+    #[account(mut, seeds = [SESSION_ESCROW_SEED, session.key().as_ref()], bump)]
+    pub session_escrow: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ LaunchError::Unauthorized,
+        seeds = [SESSION_SEED, owner.key().as_ref(), session.session_key.as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, SessionAuthority>,
+
+    #[account(mut, seeds = [SESSION_ESCROW_SEED, session.key().as_ref()], bump)]
+    pub session_escrow: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BuyTokensWithSession<'info> {
+    #[account(mut)]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        mut,
+        has_one = owner @ LaunchError::Unauthorized,
+        has_one = session_key @ LaunchError::Unauthorized,
+        seeds = [SESSION_SEED, owner.key().as_ref(), session_key.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Account<'info, SessionAuthority>,
+
+    #[account(mut)]
+    pub session_key: Signer<'info>,
+
+    // Holds the session's escrowed spend cap and pays its buys
+    #[account(mut, seeds = [SESSION_ESCROW_SEED, session.key().as_ref()], bump)]
+    pub session_escrow: SystemAccount<'info>,
+
+    /// CHECK: Session owner receiving the tokens, validated against the session
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Blacklist PDA for the session owner, which must not exist
+    #[account(seeds = [BLACKLIST_SEED, owner.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+
+    // Required, so the owner's daily cap binds every session key and session buys keep the
+    // owner's cost basis, loyalty and rewards like any other buy
     #[account(
         init_if_needed,
-        payer = buyer,
-        associated_token::mint = mint,
-        associated_token::authority = buyer,
+        payer = rent_payer,
+        space = BuyerState::LEN,
+        seeds = [BUYER_STATE_SEED, launch.key().as_ref(), owner.key().as_ref()],
+        bump
     )]
-    pub buyer_token_account: Account<'info, TokenAccount>,
-    
+    pub owner_buyer_state: Account<'info, BuyerState>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [STATS_SEED, launch.key().as_ref()],
+        bump = stats.bump
+    )]
+    pub stats: Account<'info, LaunchStats>,
+
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: Integrator frontend that routed the trade, paid a share of the fee
+    #[account(mut)]
+    pub frontend_fee_recipient: Option<UncheckedAccount<'info>>,
+
+    #[account(mut, address = launch.mint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: PDA for mint authority
+    #[account(
+        seeds = [MINT_AUTHORITY_SEED, launch.key().as_ref()],
+        bump
+    )]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Owner's ATA, created idempotently and validated in the handler
+    #[account(mut)]
+    pub owner_token_account: UncheckedAccount<'info>,
+
+    // Pays ATA rent on a first buy; may be the session key or a sponsor
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+
+    // Required when the config sets an attestation mint
+    pub attestation_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the launch gates buys on holding another mint
+    pub gate_token_account: Option<Account<'info, TokenAccount>>,
+
+    // Required when the launch runs a raffle
+    #[account(
+        mut,
+        seeds = [RAFFLE_SEED, launch.key().as_ref()],
+        bump = raffle.bump
+    )]
+    pub raffle: Option<Account<'info, Raffle>>,
+
+    // Required when the launch only accepts freshly assembled transactions
+    /// CHECK: Instructions sysvar, scanned for a prove_freshness instruction
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    // Required when the launch has a trade hook
+    /// CHECK: Hook program, validated against the launch and the allow-list
+    #[account(executable, address = launch.hook_program @ LaunchError::HookNotAllowed)]
+    pub hook_program: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        seeds = [HOOK_ALLOWLIST_SEED, launch.hook_program.as_ref()],
+        bump = hook_allowlist_entry.bump
+    )]
+    pub hook_allowlist_entry: Option<Account<'info, HookAllowlistEntry>>,
+
+    // Required when the launch records trades in a compressed tree
+    /// CHECK: Concurrent merkle tree, validated against the launch
+    #[account(mut, address = launch.trade_tree @ LaunchError::InvalidTradeTree)]
+    pub trade_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: PDA authority over the launch's trade tree
+    #[account(seeds = [TREE_AUTHORITY_SEED, launch.key().as_ref()], bump)]
+    pub tree_authority: Option<UncheckedAccount<'info>>,
+
+    pub compression_program: Option<Program<'info, SplAccountCompression>>,
+    pub noop_program: Option<Program<'info, Noop>>,
+
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
-#[account]
-pub struct Launch {
-    pub creator: Pubkey,
-    pub mint: Pubkey,
-    pub name: String,
-    pub symbol: String,
-    pub initial_supply: u64,
-    pub total_supply: u64,
-    pub curve_ratio: u64,
-    pub is_active: bool,
+#[account]
+#[derive(Default)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub sweep_bounty_bps: u16,
+    pub trade_fee_bps: u16,
+    pub dynamic_fee_enabled: bool,
+    pub max_dynamic_fee_bps: u16,
+    pub velocity_window_secs: i64,
+    pub velocity_threshold: u64,
+    pub attestation_mint: Pubkey,
+    pub withdrawal_timelock_secs: i64,
+    pub graduation_min_cu_price: u64,
+    pub graduation_bounty: u64,
+    pub min_trade_lamports: u64,
+    pub min_trade_tokens: u64,
+    pub pending_admin: Pubkey,
+    pub admin_accept_after: i64,
+    pub max_supply_delta_bps: u16,
+    pub max_tx_age_slots: u64,
+    pub frontend_fee_bps: u16,
+    pub min_graduation_market_cap: u64,
+    pub max_graduation_market_cap: u64,
+    pub market_maker_window_secs: i64,
+    // Uppercase symbols, zero-padded
+    pub reserved_symbols: [[u8; MAX_SYMBOL_LEN]; MAX_RESERVED_SYMBOLS],
+    pub reserved_symbol_count: u8,
+    pub sell_fee_bps: Option<u16>,
+    // Only destination for treasury withdrawals; set once
+    pub cold_wallet: Pubkey,
+    // Lifetime trading fees claimed from launch vaults into the treasury
+    pub protocol_fees_claimed: u64,
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    pub fee_tier_count: u8,
+    // Fee discount for holders of the protocol token; disabled while the mint is unset
+    pub protocol_token_mint: Pubkey,
+    pub protocol_discount_min_balance: u64,
+    pub protocol_discount_bps: u16,
+    // Owner program of creator profile PDAs accepted by set_creator_identity
+    pub profile_program: Pubkey,
+    // Swap program for fee buybacks, and lifetime protocol tokens burned through it
+    pub buyback_dex_program: Pubkey,
+    pub protocol_tokens_burned: u64,
+    // Reserves share seeded into a DEX pool at graduation, and the program creating it
+    pub graduation_liquidity_bps: u16,
+    pub graduation_dex_program: Pubkey,
+    // Meteora DLMM program for launches graduating into concentrated liquidity
+    pub dlmm_program: Pubkey,
+    // Launch supply limits enforced by LaunchParams::validate; zero disables each
+    pub max_initial_supply: u64,
+    pub max_total_supply: u64,
+    pub max_initial_supply_bps: u16,
+    // Latest trading-competition epoch and when it stops counting volume
+    pub competition_epoch: u32,
+    pub competition_ends_at: i64,
+    // SPL Name Service program and the parent account of every .sol domain on this cluster
+    pub name_service_program: Pubkey,
+    pub sol_tld_authority: Pubkey,
+    pub bump: u8,
+}
+
+impl Config {
+    const LEN: usize = 8 + // discriminator
+        32 + // admin
+        32 + // treasury
+        2 + // protocol_fee_bps
+        2 + // sweep_bounty_bps
+        2 + // trade_fee_bps
+        1 + // dynamic_fee_enabled
+        2 + // max_dynamic_fee_bps
+        8 + // velocity_window_secs
+        8 + // velocity_threshold
+        32 + // attestation_mint
+        8 + // withdrawal_timelock_secs
+        8 + // graduation_min_cu_price
+        8 + // graduation_bounty
+        8 + // min_trade_lamports
+        8 + // min_trade_tokens
+        32 + // pending_admin
+        8 + // admin_accept_after
+        2 + // max_supply_delta_bps
+        8 + // max_tx_age_slots
+        2 + // frontend_fee_bps
+        8 + // min_graduation_market_cap
+        8 + // max_graduation_market_cap
+        8 + // market_maker_window_secs
+        MAX_RESERVED_SYMBOLS * MAX_SYMBOL_LEN + // reserved_symbols
+        1 + // reserved_symbol_count
+        1 + 2 + // sell_fee_bps
+        32 + // cold_wallet
+        8 + // protocol_fees_claimed
+        MAX_FEE_TIERS * FeeTier::LEN + // fee_tiers
+        1 + // fee_tier_count
+        32 + // protocol_token_mint
+        8 + // protocol_discount_min_balance
+        2 + // protocol_discount_bps
+        32 + // profile_program
+        32 + // buyback_dex_program
+        8 + // protocol_tokens_burned
+        2 + // graduation_liquidity_bps
+        32 + // graduation_dex_program
+        32 + // dlmm_program
+        8 + // max_initial_supply
+        8 + // max_total_supply
+        2 + // max_initial_supply_bps
+        4 + // competition_epoch
+        8 + // competition_ends_at
+        32 + // name_service_program
+        32 + // sol_tld_authority
+        1; // bump
+
+    // Add a tracked trade to the wallet's volume for the competition epoch running now,
+    // starting the count over the first time the wallet trades in a new epoch
+    // DEX program a graduation into `venue` creates its pool through
+    fn graduation_dex(&self, venue: GraduationVenue) -> Pubkey {
+        match venue {
+            GraduationVenue::Pool => self.graduation_dex_program,
+            GraduationVenue::Dlmm => self.dlmm_program,
+        }
+    }
+
+    fn record_competition_volume(&self, holder: &mut BuyerState, lamports: u64, now: i64) {
+        if self.competition_epoch == 0 || now >= self.competition_ends_at {
+            return;
+        }
+        if holder.competition_epoch != self.competition_epoch {
+            holder.competition_epoch = self.competition_epoch;
+            holder.competition_volume = 0;
+        }
+        holder.competition_volume = holder.competition_volume.saturating_add(lamports);
+    }
+
+    fn is_reserved_symbol(&self, symbol: &str) -> bool {
+        let symbol = symbol.as_bytes();
+        self.reserved_symbols[..self.reserved_symbol_count as usize]
+            .iter()
+            .any(|reserved| {
+                let len = reserved.iter().position(|b| *b == 0).unwrap_or(MAX_SYMBOL_LEN);
+                reserved[..len].eq_ignore_ascii_case(symbol)
+            })
+    }
+
+    // Frontends only earn a share when they pass their fee account with the trade
+    fn frontend_share(&self, fee: u64, routed: bool) -> Result<u64> {
+        if !routed {
+            return Ok(0);
+        }
+        bps_of(fee, self.frontend_fee_bps)
+    }
+
+    // Reject dust trades that would only spam stats and events
+    fn check_trade_size(&self, amount: u64, lamports: u64) -> Result<()> {
+        require!(
+            amount >= self.min_trade_tokens && lamports >= self.min_trade_lamports,
+            LaunchError::TradeTooSmall
+        );
+        Ok(())
+    }
+
+    // Compliant deployments require buyers to hold a token from the configured attestation mint
+    fn verify_attestation(&self, attestation: Option<&TokenAccount>, holder: &Pubkey) -> Result<()> {
+        if self.attestation_mint == Pubkey::default() {
+            return Ok(());
+        }
+
+        let attestation = attestation.ok_or(LaunchError::AttestationRequired)?;
+        require_keys_eq!(attestation.mint, self.attestation_mint, LaunchError::AttestationRequired);
+        require_keys_eq!(attestation.owner, *holder, LaunchError::AttestationRequired);
+        require!(attestation.amount > 0, LaunchError::AttestationRequired);
+        Ok(())
+    }
+
+    // trade_fee_bps is the buy fee; sells use sell_fee_bps when set. A launch override for
+    // either side takes precedence over both.
+    fn base_fee_bps(&self, launch: &Launch, is_buy: bool) -> u16 {
+        if is_buy {
+            launch.buy_fee_bps_override.unwrap_or(self.trade_fee_bps)
+        } else {
+            launch
+                .sell_fee_bps_override
+                .or(self.sell_fee_bps)
+                .unwrap_or(self.trade_fee_bps)
+        }
+    }
+
+    // Discount of the highest fee tier the launch's market cap has reached
+    fn maturity_discount_bps(&self, launch: &Launch) -> Result<u16> {
+        let tiers = &self.fee_tiers[..self.fee_tier_count as usize];
+        if tiers.is_empty() {
+            return Ok(0);
+        }
+        let market_cap = launch.market_cap()?;
+        Ok(tiers
+            .iter()
+            .rev()
+            .find(|tier| market_cap >= tier.market_cap_threshold as u128)
+            .map_or(0, |tier| tier.discount_bps))
+    }
+
+    // Protocol-token discount for `trader`, who passes their ATA of the protocol mint among
+    // the remaining accounts to claim it. It is found by address, so it can sit anywhere there.
+    fn holder_discount_bps(&self, trader: &Pubkey, remaining: &[AccountInfo]) -> Result<u16> {
+        if self.protocol_token_mint == Pubkey::default() {
+            return Ok(0);
+        }
+        let ata = associated_token::get_associated_token_address(trader, &self.protocol_token_mint);
+        let Some(info) = remaining.iter().find(|info| info.key() == ata) else {
+            return Ok(0);
+        };
+
+        // An ATA's owner can be reassigned, so check it still belongs to the trader
+        require_keys_eq!(*info.owner, token::ID, LaunchError::InvalidTokenAccount);
+        let holding = TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        let eligible = holding.owner == *trader
+            && holding.mint == self.protocol_token_mint
+            && holding.amount >= self.protocol_discount_min_balance;
+        Ok(if eligible { self.protocol_discount_bps } else { 0 })
+    }
+
+    // Trading fee for the next trade, after any maturity discount; in dynamic mode it scales
+    // linearly from there to the max fee as windowed volume approaches the velocity threshold.
+    // A holder discount comes off the final fee.
+    fn trade_fee_bps(
+        &self,
+        launch: &Launch,
+        is_buy: bool,
+        stats: &LaunchStats,
+        holder_discount_bps: u16,
+    ) -> Result<u16> {
+        let base = self
+            .base_fee_bps(launch, is_buy)
+            .saturating_sub(self.maturity_discount_bps(launch)?);
+        if !self.dynamic_fee_enabled || self.velocity_threshold == 0 {
+            return Ok(base.saturating_sub(holder_discount_bps));
+        }
+
+        let span = self.max_dynamic_fee_bps.saturating_sub(base) as u128;
+        let volume = stats.window_volume.min(self.velocity_threshold) as u128;
+        let fee_bps = base + (span * volume / self.velocity_threshold as u128) as u16;
+        Ok(fee_bps.saturating_sub(holder_discount_bps))
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct Launch {
+    pub creator: Pubkey,
+    pub fee_destination: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub initial_supply: u64,
+    pub total_supply: u64,
+    pub curve_ratio: u64,
+    pub max_supply: u64,
+    pub state: LaunchState,
+    pub milestone_count: u8,
+    pub milestones: [Milestone; MAX_MILESTONES],
+    pub graduated_at: i64,
+    pub total_distributed: u64,
+    pub snapshot_count: u32,
+    pub proposal_count: u32,
+    pub approved_metadata_hash: [u8; 32],
+    pub raise_target: u64,
+    pub raise_deadline: i64,
+    pub raised: u64,
+    pub metadata_uri: String,
+    pub metadata_hash: [u8; 32],
+    pub trade_seq: u64,
+    pub total_donations: u64,
+    pub raffle_enabled: bool,
+    pub max_wallet_bps: u16,
+    pub max_wallet_until: i64,
+    pub trading_opens_at: i64,
+    pub reservation_count: u32,
+    pub reservations_settled: u32,
+    // Tokens filled by reservations, which sit flat at the base price above the initial supply
+    pub reserved_supply: u64,
+    pub curve_stage_count: u8,
+    pub curve_stages: [CurveStage; MAX_CURVE_STAGES],
+    pub withdrawal_requested_at: i64,
+    pub withdrawal_request_amount: u64,
+    pub previous_launch: Pubkey,
+    pub airdrop_root: [u8; 32],
+    pub airdrop_supply: u64,
+    pub airdrop_claimed: u64,
+    pub sol_reserves: u64,
+    pub bundle_leader: Pubkey,
+    pub flat_sale_start: u64,
+    pub final_price: u64,
+    pub rebate_bps: u16,
+    pub rebate_emission_cap: u64,
+    pub rebate_emitted: u64,
+    pub curve_type: CurveType,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub hook_program: Pubkey,
+    pub trade_tree: Pubkey,
+    pub deed_mint: Pubkey,
+    pub delta_slot: u64,
+    pub delta_slot_supply: u64,
+    pub fresh_tx_required: bool,
+    pub airdrop_expires_at: i64,
+    pub immutable_metadata: bool,
+    pub graduation_market_cap: u64,
+    // Mint decimals, fixed at creation; curve math converts base units to whole tokens with it
+    pub decimals: u8,
+    // Set when the launch first moves to Trading
+    pub trading_started_at: i64,
+    // Slots over which the raise streams to the creator after graduation; zero disables
+    pub stream_slots: u64,
+    pub graduated_slot: u64,
+    // Circuit breaker on price crashes; a zero drop disables it
+    pub breaker_drop_bps: u16,
+    pub breaker_window_secs: i64,
+    pub breaker_pause_secs: i64,
+    // Co-signer for privileged operations on high-security launches; default when unset
+    pub secondary_authority: Pubkey,
+    // Set while a trade's CPIs run; see process_buy
+    pub trade_in_flight: bool,
+    // Creator deposit in the vault, outside sol_reserves, refunded at graduation
+    pub bootstrap_lamports: u64,
+    // Time-weighted holding across all tracked holders, closed when a reward round is funded
+    pub loyalty_supply: u64,
+    pub loyalty_points: u128,
+    pub loyalty_updated_at: i64,
+    pub loyalty_ends_at: i64,
+    pub loyalty_reward_lamports: u64,
+    pub early_supporter_limit: u16,
+    pub early_supporter_count: u16,
+    // Admin-set fee overrides for this launch
+    pub buy_fee_bps_override: Option<u16>,
+    pub sell_fee_bps_override: Option<u16>,
+    // Hash of the latest committed tokenomics document and how many revisions exist
+    pub tokenomics_hash: [u8; 32],
+    pub tokenomics_version: u32,
+    // Treasury fee share held in the vault until claimed, outside sol_reserves
+    pub accrued_protocol_fees: u64,
+    // Set by the admin on impersonation reports; a reported launch's metadata is frozen
+    pub reported: bool,
+    pub metadata_frozen: bool,
+    // Buyers must hold at least gate_min_balance of gate_mint when it is set
+    pub gate_mint: Pubkey,
+    pub gate_min_balance: u64,
+    // Fair-mint window, pooled deposits and the tokens they were allocated at finalization
+    pub fair_mint_supply: u64,
+    pub fair_mint_ends_at: i64,
+    pub fair_mint_deposits: u64,
+    pub fair_mint_allocated: u64,
+    pub fair_mint_finalized: bool,
+    // Booked on the curve but not yet minted: pledges and fair-mint shares awaiting claim
+    pub unminted_supply: u64,
+    // Hot key the creator delegated OPERATOR_* permissions to
+    pub operator: Pubkey,
+    pub operator_permissions: u8,
+    // Sum of every tracked holder's cost basis, and what claim_refund has paid out of it
+    pub total_cost_basis: u64,
+    pub refunded_lamports: u64,
+    // Name record or profile PDA the creator proved ownership of, and when
+    pub creator_identity: Pubkey,
+    pub creator_identity_kind: CreatorIdentityKind,
+    pub creator_identity_verified_at: i64,
+    // Share of claimed protocol fees sent to the buyback pool
+    pub buyback_bps: u16,
+    // Liquidity fixed by begin_graduation and how much of its SOL has been deposited so far
+    pub graduation_liquidity_lamports: u64,
+    pub graduation_liquidity_tokens: u64,
+    pub liquidity_deposited: u64,
+    pub graduation_venue: GraduationVenue,
+    // Buys starting below this supply earn LP claim units; zero disables them
+    pub lp_unit_cutoff_supply: u64,
+    pub total_lp_units: u64,
+    // Lifetime LP fee revenue paid into the LP reward pool
+    pub lp_rewards_funded: u64,
+    pub residual_policy: ResidualPolicy,
+    pub residual_vesting_secs: i64,
+    // Unsold supply minted at graduation under the residual policy
+    pub residual_tokens: u64,
+    // Vested residual tokens already released to the creator
+    pub residual_claimed: u64,
+    // Lifetime treasury lamports added to reserves for incident recovery
+    pub treasury_top_ups: u64,
+}
+
+impl Launch {
+    const LEN: usize = 8 + // discriminator
+        32 + // creator
+        32 + // fee_destination
+        32 + // mint
+        4 + MAX_NAME_LEN + // name
+        4 + MAX_SYMBOL_LEN + // symbol
+        8 + // initial_supply
+        8 + // total_supply
+        8 + // curve_ratio
+        8 + // max_supply
+        1 + // state
+        1 + // milestone_count
+        MAX_MILESTONES * Milestone::LEN + // milestones
+        8 + // graduated_at
+        8 + // total_distributed
+        4 + // snapshot_count
+        4 + // proposal_count
+        32 + // approved_metadata_hash
+        8 + // raise_target
+        8 + // raise_deadline
+        8 + // raised
+        4 + MAX_URI_LEN + // metadata_uri
+        32 + // metadata_hash
+        8 + // trade_seq
+        8 + // total_donations
+        1 + // raffle_enabled
+        2 + // max_wallet_bps
+        8 + // max_wallet_until
+        8 + // trading_opens_at
+        4 + // reservation_count
+        4 + // reservations_settled
+        8 + // reserved_supply
+        1 + // curve_stage_count
+        MAX_CURVE_STAGES * CurveStage::LEN + // curve_stages
+        8 + // withdrawal_requested_at
+        8 + // withdrawal_request_amount
+        32 + // previous_launch
+        32 + // airdrop_root
+        8 + // airdrop_supply
+        8 + // airdrop_claimed
+        8 + // sol_reserves
+        32 + // bundle_leader
+        8 + // flat_sale_start
+        8 + // final_price
+        2 + // rebate_bps
+        8 + // rebate_emission_cap
+        8 + // rebate_emitted
+        1 + // curve_type
+        8 + // virtual_sol_reserves
+        8 + // virtual_token_reserves
+        32 + // hook_program
+        32 + // trade_tree
+        32 + // deed_mint
+        8 + // delta_slot
+        8 + // delta_slot_supply
+        1 + // fresh_tx_required
+        8 + // airdrop_expires_at
+        1 + // immutable_metadata
+        8 + // graduation_market_cap
+        1 + // decimals
+        8 + // trading_started_at
+        8 + // stream_slots
+        8 + // graduated_slot
+        2 + // breaker_drop_bps
+        8 + // breaker_window_secs
+        8 + // breaker_pause_secs
+        32 + // secondary_authority
+        1 + // trade_in_flight
+        8 + // bootstrap_lamports
+        8 + // loyalty_supply
+        16 + // loyalty_points
+        8 + // loyalty_updated_at
+        8 + // loyalty_ends_at
+        8 + // loyalty_reward_lamports
+        2 + // early_supporter_limit
+        2 + // early_supporter_count
+        1 + 2 + // buy_fee_bps_override
+        1 + 2 + // sell_fee_bps_override
+        32 + // tokenomics_hash
+        4 + // tokenomics_version
+        8 + // accrued_protocol_fees
+        1 + // reported
+        1 + // metadata_frozen
+        32 + // gate_mint
+        8 + // gate_min_balance
+        8 + // fair_mint_supply
+        8 + // fair_mint_ends_at
+        8 + // fair_mint_deposits
+        8 + // fair_mint_allocated
+        1 + // fair_mint_finalized
+        8 + // unminted_supply
+        32 + // operator
+        1 + // operator_permissions
+        8 + // total_cost_basis
+        8 + // refunded_lamports
+        32 + // creator_identity
+        1 + // creator_identity_kind
+        8 + // creator_identity_verified_at
+        2 + // buyback_bps
+        8 + // graduation_liquidity_lamports
+        8 + // graduation_liquidity_tokens
+        8 + // liquidity_deposited
+        1 + // graduation_venue
+        8 + // lp_unit_cutoff_supply
+        8 + // total_lp_units
+        8 + // lp_rewards_funded
+        1 + // residual_policy
+        8 + // residual_vesting_secs
+        8 + // residual_tokens
+        8 + // residual_claimed
+        8; // treasury_top_ups
+
+    fn init(
+        &mut self,
+        config: &Config,
+        creator: Pubkey,
+        mint: Pubkey,
+        params: LaunchParams,
+    ) -> Result<()> {
+        params.validate(config)?;
+
+        self.creator = creator;
+        self.fee_destination = creator;
+        self.mint = mint;
+        self.name = params.name;
+        self.symbol = params.symbol;
+        self.initial_supply = params.initial_supply;
+        self.curve_ratio = params.curve_ratio;
+        self.total_supply = params.initial_supply;
+        self.max_supply = params.max_supply;
+        self.immutable_metadata = params.immutable_metadata;
+        self.metadata_uri = params.metadata_uri;
+        self.metadata_hash = params.metadata_hash;
+        self.graduation_market_cap = params.graduation_market_cap;
+        self.residual_policy = params.residual_policy;
+        self.residual_vesting_secs = params.residual_vesting_secs;
+        self.decimals = TOKEN_DECIMALS;
+        self.state = if params.initial_supply == params.max_supply {
+            LaunchState::CurveComplete
+        } else {
+            LaunchState::Pending
+        };
+        Ok(())
+    }
+
+    // Validate a buy of `amount` tokens and return the resulting supply and its price
+    fn quote_buy(&mut self, amount: u64, now: i64) -> Result<(u64, u64)> {
+        self.require_trading_open(now)?;
+        self.quote_allocation(amount)
+    }
+
+    // Price the next `amount` tokens on the curve, shared by buys and crowdfund pledges
+    fn quote_allocation(&self, amount: u64) -> Result<(u64, u64)> {
+        self.require_curve_open()?;
+
+        let new_supply = self
+            .total_supply
+            .checked_add(amount)
+            .ok_or(LaunchError::MaxSupplyExceeded)?;
+        require!(new_supply <= self.max_supply, LaunchError::MaxSupplyExceeded);
+
+        // Calculate price based on bonding curve
+        let price = self.curve_cost(self.total_supply, amount, Rounding::Up)?;
+        Ok((new_supply, price))
+    }
+
+    // Validate a sell of `amount` tokens and return the resulting supply and its proceeds
+    fn quote_sell(&mut self, amount: u64, now: i64) -> Result<(u64, u64)> {
+        self.require_trading_open(now)?;
+
+        // Only tokens sold by the curve can be sold back into it
+        let new_supply = self
+            .total_supply
+            .checked_sub(amount)
+            .filter(|supply| *supply >= self.initial_supply)
+            .ok_or(LaunchError::InsufficientReserves)?;
+
+        // Proceeds mirror the price paid to buy the same tokens back up to the current supply
+        let proceeds = self.curve_cost(new_supply, amount, Rounding::Down)?;
+        Ok((new_supply, proceeds))
+    }
+
+    // Public trading waits for the crowdfund target, the open time and every reservation fill.
+    // The first trade after those gates clear moves a pending launch into trading.
+    fn require_trading_open(&mut self, now: i64) -> Result<()> {
+        self.require_curve_open()?;
+        if self.state == LaunchState::Pending {
+            require!(!self.crowdfund_pending(), LaunchError::CrowdfundPending);
+            require!(
+                self.fair_mint_ends_at == 0 || self.fair_mint_finalized,
+                LaunchError::FairMintPending
+            );
+            require!(now >= self.trading_opens_at, LaunchError::TradingNotStarted);
+            require!(
+                self.reservations_settled == self.reservation_count,
+                LaunchError::ReservationsPending
+            );
+            self.transition(LaunchState::Trading)?;
+            self.trading_started_at = now;
+        }
+        Ok(())
+    }
+
+    // The curve takes allocations while pending (pledges, reservations) and while trading
+    fn require_curve_open(&self) -> Result<()> {
+        match self.state {
+            LaunchState::Pending | LaunchState::Trading => Ok(()),
+            LaunchState::CurveComplete => err!(LaunchError::CurveComplete),
+            _ => err!(LaunchError::LaunchInactive),
+        }
+    }
+
+    // Most tokens `lamports` buys on the curve from the current supply, up to `limit`
+    fn affordable_amount(&self, lamports: u64, limit: u64) -> Result<u64> {
+        let (mut low, mut high) = (0, limit.min(self.max_supply - self.total_supply));
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if self.curve_cost(self.total_supply, mid, Rounding::Up)? <= lamports {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Ok(low)
+    }
+
+    // Configured stages as (supply_threshold, multiplier_bps), for the curve module
+    fn stages(&self) -> impl Iterator<Item = (u64, u16)> + Clone + '_ {
+        self.curve_stages[..self.curve_stage_count as usize]
+            .iter()
+            .map(|stage| (stage.supply_threshold, stage.multiplier_bps))
+    }
+
+    // Price `amount` tokens starting at `start_supply`. Reservation fills sit flat at the base
+    // price they were filled at, right above the initial supply, and the curve proper resumes
+    // above them, so supply bought before a fill keeps its price. Every segment is priced by
+    // its exact area, buys rounded up and sells rounded down, so selling tokens back never pays
+    // out more than buying them cost, however either side is split into trades. The round
+    // trips in the tests below pin this for each curve type.
+    fn curve_cost(&self, start_supply: u64, amount: u64, rounding: Rounding) -> Result<u64> {
+        let reserved_end = self.initial_supply + self.reserved_supply;
+        let reserved_units = reserved_end.saturating_sub(start_supply).min(amount);
+        let curve_start = start_supply.max(reserved_end) - self.reserved_supply;
+        if reserved_units == 0 {
+            return self.unreserved_cost(curve_start, amount, rounding);
+        }
+
+        let reserved_cost: u64 = rounding
+            .div(
+                reserved_units as u128 * self.base_token_price()? as u128,
+                self.token_unit() as u128,
+            )
+            .try_into()
+            .map_err(|_| LaunchError::InvalidPriceCalculation)?;
+        if reserved_units == amount {
+            return Ok(reserved_cost);
+        }
+        Ok(self
+            .unreserved_cost(curve_start, amount - reserved_units, rounding)?
+            .checked_add(reserved_cost)
+            .ok_or(LaunchError::InvalidPriceCalculation)?)
+    }
+
+    // Price `amount` tokens on the curve proper, from `start_supply` with the reserved supply
+    // taken out, flat beyond the flat-sale start
+    fn unreserved_cost(&self, start_supply: u64, amount: u64, rounding: Rounding) -> Result<u64> {
+        if self.curve_type == CurveType::VirtualReserves {
+            return self.virtual_reserves_cost(start_supply, amount, rounding);
+        }
+
+        let end_supply = start_supply
+            .checked_add(amount)
+            .ok_or(LaunchError::InvalidPriceCalculation)?;
+        if self.flat_sale_start == 0 || end_supply <= self.flat_sale_start {
+            return self.staged_cost(start_supply, amount, rounding);
+        }
+
+        let curve_units = self.flat_sale_start.saturating_sub(start_supply);
+        let flat_units = (amount - curve_units) as u128;
+        let flat_cost: u64 = rounding
+            .div(
+                flat_units * self.flat_token_price(rounding)? as u128,
+                self.token_unit() as u128,
+            )
+            .try_into()
+            .map_err(|_| LaunchError::InvalidPriceCalculation)?;
+        Ok(self
+            .staged_cost(start_supply, curve_units, rounding)?
+            .checked_add(flat_cost)
+            .ok_or(LaunchError::InvalidPriceCalculation)?)
+    }
+
+    // Constant-product cost between two supplies, so buys and sells over the same range match
+    fn virtual_reserves_cost(
+        &self,
+        start_supply: u64,
+        amount: u64,
+        rounding: Rounding,
+    ) -> Result<u64> {
+        Ok(curve::virtual_reserves_cost(
+            self.virtual_sol_reserves,
+            self.virtual_token_reserves,
+            self.initial_supply,
+            start_supply,
+            amount,
+            rounding,
+        )
+        .ok_or(LaunchError::InvalidPriceCalculation)?)
+    }
+
+    // Final curve price per whole token, charged for everything sold past the flat-sale start
+    fn flat_token_price(&self, rounding: Rounding) -> Result<u64> {
+        self.staged_cost(self.flat_sale_start, self.token_unit(), rounding)
+    }
+
+    // Price `amount` tokens starting at `start_supply`, splitting the range at stage thresholds
+    fn staged_cost(&self, start_supply: u64, amount: u64, rounding: Rounding) -> Result<u64> {
+        let cost = curve::staged_cost(
+            self.curve_ratio,
+            self.stages(),
+            self.decimals,
+            start_supply,
+            amount,
+            rounding,
+        );
+        Ok(cost.ok_or(LaunchError::InvalidPriceCalculation)?)
+    }
+
+    // Base units per whole token
+    fn token_unit(&self) -> u64 {
+        10u64.pow(self.decimals as u32)
+    }
+
+    // Price of the next whole token at the current supply, rounded down; for display and
+    // thresholds, never for charging
+    fn spot_price(&self) -> Result<u64> {
+        self.curve_cost(self.total_supply, self.token_unit(), Rounding::Down)
+    }
+
+    // Spot price times circulating supply, in lamports
+    fn market_cap(&self) -> Result<u128> {
+        Ok(self.spot_price()? as u128 * self.total_supply as u128 / self.token_unit() as u128)
+    }
+
+    fn graduation_target_reached(&self) -> Result<bool> {
+        if self.graduation_market_cap == 0 {
+            return Ok(false);
+        }
+        Ok(self.market_cap()? >= self.graduation_market_cap as u128)
+    }
+
+    fn set_graduation_venue(&mut self, config: &Config, venue: GraduationVenue) -> Result<()> {
+        require!(
+            matches!(
+                self.state,
+                LaunchState::Pending | LaunchState::Trading | LaunchState::CurveComplete
+            ),
+            LaunchError::AlreadyGraduated
+        );
+        require!(
+            config.graduation_dex(venue) != Pubkey::default(),
+            LaunchError::GraduationVenueUnavailable
+        );
+        self.graduation_venue = venue;
+        Ok(())
+    }
+
+    // Whole-token price at the initial supply, used to fill pre-launch reservations
+    fn base_token_price(&self) -> Result<u64> {
+        let price = self.unreserved_cost(self.initial_supply, self.token_unit(), Rounding::Up)?;
+        require!(price > 0, LaunchError::InvalidPriceCalculation);
+        Ok(price)
+    }
+
+    // Fill a reservation of `lamports` at the base price, capped by the supply left on the
+    // curve, into the reserved range. Returns the tokens filled and the lamports they cost.
+    fn fill_reservation(&mut self, lamports: u64) -> Result<(u64, u64)> {
+        let token_price = self.base_token_price()? as u128;
+        let token_unit = self.token_unit() as u128;
+        let affordable: u64 = (lamports as u128 * token_unit / token_price)
+            .try_into()
+            .map_err(|_| LaunchError::InvalidPriceCalculation)?;
+        let amount = affordable.min(self.max_supply.saturating_sub(self.total_supply));
+        let cost: u64 = (amount as u128 * token_price)
+            .div_ceil(token_unit)
+            .try_into()
+            .map_err(|_| LaunchError::InvalidPriceCalculation)?;
+
+        if amount > 0 {
+            self.reserved_supply += amount;
+            self.record_buy(self.total_supply + amount, cost)?;
+        }
+        Ok((amount, cost))
+    }
+
+    // An all-or-nothing launch stays closed to trading until its raise target is met
+    fn crowdfund_pending(&self) -> bool {
+        self.raise_target > 0 && self.raised < self.raise_target
+    }
+
+    fn crowdfund_failed(&self, now: i64) -> bool {
+        self.crowdfund_pending() && now >= self.raise_deadline
+    }
+
+    // Lamports that may leave the vault now, given what is still held in it
+    fn releasable(&self, vault_balance: u64, clock: &Clock) -> Result<u64> {
+        // Reserves back sells until the launch graduates
+        if self.state != LaunchState::Graduated {
+            return Ok(0);
+        }
+        let gross = vault_balance.saturating_add(self.total_distributed);
+        if self.stream_slots > 0 {
+            let elapsed = clock.slot.saturating_sub(self.graduated_slot).min(self.stream_slots);
+            let unlocked = (gross as u128 * elapsed as u128 / self.stream_slots as u128) as u64;
+            return Ok(unlocked.saturating_sub(self.total_distributed).min(vault_balance));
+        }
+        if self.milestone_count == 0 {
+            return Ok(vault_balance);
+        }
+
+        let now = clock.unix_timestamp;
+
+        let unlocked_bps: u16 = self.milestones[..self.milestone_count as usize]
+            .iter()
+            .filter(|m| now >= self.graduated_at.saturating_add(m.unlock_after_secs))
+            .map(|m| m.bps)
+            .sum();
+        let unlocked = bps_of(gross, unlocked_bps)?;
+        Ok(unlocked.saturating_sub(self.total_distributed).min(vault_balance))
+    }
+
+    // Tokens rebated out of `fee` at the post-trade price, and the fee lamports that buy them.
+    // Those lamports go to reserves so rebated tokens are backed like any other buy.
+    fn quote_rebate(&self, fee: u64, new_supply: u64) -> Result<(u64, u64)> {
+        if self.rebate_bps == 0 {
+            return Ok((0, 0));
+        }
+
+        let share = bps_of(fee, self.rebate_bps)?;
+        let token_price = self.curve_cost(new_supply, self.token_unit(), Rounding::Up)?.max(1);
+        let remaining = self
+            .rebate_emission_cap
+            .saturating_sub(self.rebate_emitted)
+            .min(self.max_supply - new_supply);
+        let affordable = share as u128 * self.token_unit() as u128 / token_price as u128;
+        let tokens = affordable.min(remaining as u128) as u64;
+        if tokens == 0 {
+            return Ok((0, 0));
+        }
+
+        let lamports = self.curve_cost(new_supply, tokens, Rounding::Up)?;
+        if lamports > share {
+            return Ok((0, 0));
+        }
+        Ok((tokens, lamports))
+    }
+
+    // Record a raffle entry for the trader and return the slice of `fee` owed to the prize pool
+    fn enter_raffle(&self, raffle: Option<&mut Raffle>, trader: Pubkey, fee: u64) -> Result<u64> {
+        if !self.raffle_enabled {
+            return Ok(0);
+        }
+
+        let raffle = raffle.ok_or(LaunchError::RaffleAccountRequired)?;
+        raffle.record_entry(trader);
+        bps_of(fee, raffle.fee_bps)
+    }
+
+    fn airdrop_expired(&self, now: i64) -> bool {
+        self.airdrop_expires_at != 0 && now >= self.airdrop_expires_at
+    }
+
+    // Measure supply movement against the supply at the start of the current slot, which
+    // bounds a transaction and everything landing alongside it
+    fn check_supply_delta(&mut self, new_supply: u64, max_delta_bps: u16) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        if self.delta_slot != slot {
+            self.delta_slot = slot;
+            self.delta_slot_supply = self.total_supply;
+        }
+        if max_delta_bps == 0 {
+            return Ok(());
+        }
+
+        let limit = bps_of(self.max_supply, max_delta_bps)?;
+        require!(
+            new_supply.abs_diff(self.delta_slot_supply) <= limit,
+            LaunchError::SupplyDeltaExceeded
+        );
+        Ok(())
+    }
+
+    // A market-maker entry only counts once the launch has traded for `window_secs`, so
+    // sniping protections still hold in the opening minutes
+    fn market_maker<'a>(
+        &self,
+        entry: Option<&'a MarketMakerEntry>,
+        window_secs: i64,
+        now: i64,
+    ) -> Option<&'a MarketMakerEntry> {
+        entry.filter(|_| {
+            self.state == LaunchState::Trading
+                && now >= self.trading_started_at.saturating_add(window_secs)
+        })
+    }
+
+    fn accrue_loyalty(&mut self, now: i64) {
+        let until = if self.loyalty_ends_at > 0 { now.min(self.loyalty_ends_at) } else { now };
+        self.loyalty_points = self
+            .loyalty_points
+            .saturating_add(holding_points(self.loyalty_supply, self.loyalty_updated_at, until));
+        self.loyalty_updated_at = self.loyalty_updated_at.max(until);
+    }
+
+    // Accrue points up to `now`, then move the holder's tracked balance to `balance`.
+    // Balances freeze once the reward round is funded.
+    fn sync_loyalty(&mut self, holder: &mut BuyerState, balance: u64, now: i64) {
+        self.accrue_loyalty(now);
+        holder.accrue_loyalty(self.loyalty_updated_at);
+        if self.loyalty_ends_at == 0 {
+            self.loyalty_supply = self.loyalty_supply - holder.loyalty_balance + balance;
+            holder.loyalty_balance = balance;
+        }
+    }
+
+    // Every minted token is either on the curve's books or was burned by its holder, so a mint
+    // supply above what the launch accounts for means tokens were minted outside the program.
+    // Holders may burn freely, so a lower supply is not an error.
+    fn check_mint_supply(&self, mint: &Mint) -> Result<()> {
+        let accounted = self
+            .total_supply
+            .checked_sub(self.unminted_supply)
+            .ok_or(LaunchError::SupplyMismatch)?;
+        require!(mint.supply <= accounted, LaunchError::SupplyMismatch);
+        Ok(())
+    }
+
+    fn verify_holder_gate(&self, holding: Option<&TokenAccount>, holder: &Pubkey) -> Result<()> {
+        if self.gate_mint == Pubkey::default() {
+            return Ok(());
+        }
+
+        let holding = holding.ok_or(LaunchError::HolderGateNotMet)?;
+        require_keys_eq!(holding.mint, self.gate_mint, LaunchError::HolderGateNotMet);
+        require_keys_eq!(holding.owner, *holder, LaunchError::HolderGateNotMet);
+        require!(holding.amount >= self.gate_min_balance, LaunchError::HolderGateNotMet);
+        Ok(())
+    }
+
+    // Credit a tracked holder with `lamports` paid into reserves for `tokens`
+    fn add_cost_basis(&mut self, holder: &mut BuyerState, lamports: u64, tokens: u64) {
+        holder.cost_basis = holder.cost_basis.saturating_add(lamports);
+        holder.basis_tokens = holder.basis_tokens.saturating_add(tokens);
+        self.total_cost_basis = self.total_cost_basis.saturating_add(lamports);
+    }
+
+    // Release the average cost of `tokens` sold from a holder's tracked basis
+    fn remove_cost_basis(&mut self, holder: &mut BuyerState, tokens: u64) {
+        if holder.basis_tokens == 0 {
+            return;
+        }
+        let tokens = tokens.min(holder.basis_tokens);
+        let released =
+            (holder.cost_basis as u128 * tokens as u128 / holder.basis_tokens as u128) as u64;
+        holder.cost_basis -= released;
+        holder.basis_tokens -= tokens;
+        self.total_cost_basis = self.total_cost_basis.saturating_sub(released);
+    }
+
+    // Refund for `tokens` of a holder's tracked basis on a cancelled launch, released from the
+    // holder's basis. The basis total is frozen once trading stops, so every claim, partial or
+    // not, uses the same ratio.
+    fn refund_basis(&self, holder: &mut BuyerState, tokens: u64) -> u64 {
+        let tokens = tokens.min(holder.basis_tokens);
+        if tokens == 0 {
+            return 0;
+        }
+        let basis = holder.cost_basis as u128 * tokens as u128 / holder.basis_tokens as u128;
+        holder.cost_basis -= basis as u64;
+        holder.basis_tokens -= tokens;
+        let total_basis = self.total_cost_basis.max(1) as u128;
+        basis.min(basis * self.refund_pool() as u128 / total_basis) as u64
+    }
+
+    // Reserves a cancelled launch had when it stopped trading, before any refund was paid
+    fn refund_pool(&self) -> u64 {
+        self.sol_reserves.saturating_add(self.refunded_lamports)
+    }
+
+    // The creator may do anything; the operator only what its permission bits allow
+    fn require_authority(&self, authority: &Pubkey, permission: u8) -> Result<()> {
+        let operator_allowed = self.operator != Pubkey::default()
+            && *authority == self.operator
+            && self.operator_permissions & permission != 0;
+        require!(*authority == self.creator || operator_allowed, LaunchError::Unauthorized);
+        Ok(())
+    }
+
+    fn require_cosigner(&self, cosigner: Option<&Signer>) -> Result<()> {
+        if self.secondary_authority == Pubkey::default() {
+            return Ok(());
+        }
+        require!(
+            cosigner.is_some_and(|signer| signer.key() == self.secondary_authority),
+            LaunchError::MissingCosigner
+        );
+        Ok(())
+    }
+
+    // During the enforcement window no wallet may hold more than max_wallet_bps of supply
+    fn check_max_wallet(&self, balance_after: u64, now: i64) -> Result<()> {
+        if self.max_wallet_bps == 0 || now >= self.max_wallet_until {
+            return Ok(());
+        }
+
+        let cap = bps_of(self.total_supply, self.max_wallet_bps)?;
+        require!(balance_after <= cap, LaunchError::MaxWalletExceeded);
+        Ok(())
+    }
+
+    fn record_sell(&mut self, new_supply: u64, lamports: u64) {
+        self.total_supply = new_supply;
+        self.sol_reserves -= lamports;
+    }
+
+    // Sequence number for the next trade event, gap-free so indexers can detect missed events
+    fn next_trade_seq(&mut self) -> u64 {
+        let seq = self.trade_seq;
+        self.trade_seq = self.trade_seq.wrapping_add(1);
+        seq
+    }
+
+    // Reserves are tracked explicitly so payouts never count the vault's rent-exempt lamports
+    fn record_buy(&mut self, new_supply: u64, lamports: u64) -> Result<()> {
+        self.total_supply = new_supply;
+        self.sol_reserves = self.sol_reserves.saturating_add(lamports);
+
+        // Snapshot the final curve price the first time the flat sale is entered. The flat-sale
+        // start is a point on the curve proper, above any reserved supply.
+        let flat_sale_entered =
+            self.flat_sale_start > 0 && new_supply - self.reserved_supply > self.flat_sale_start;
+        if flat_sale_entered && self.final_price == 0 {
+            self.final_price = self.flat_token_price(Rounding::Down)?;
+        }
+
+        // Once the hard cap is reached the curve is closed to buys and the launch can graduate
+        if self.total_supply == self.max_supply {
+            self.transition(LaunchState::CurveComplete)?;
+        }
+        Ok(())
+    }
+
+    // Every lifecycle change goes through here so instructions cannot skip or reverse a stage
+    fn transition(&mut self, next: LaunchState) -> Result<()> {
+        use LaunchState::*;
+        let allowed = matches!(
+            (self.state, next),
+            (Pending, Trading)
+                | (Pending, CurveComplete)
+                | (Pending, Refunding)
+                | (Pending, Cancelled)
+                | (Trading, CurveComplete)
+                | (Trading, Cancelled)
+                | (CurveComplete, Graduating)
+                | (CurveComplete, Graduated)
+                | (Graduating, Graduated)
+                | (Graduated, Closed)
+                | (Refunding, Closed)
+        );
+        require!(allowed, LaunchError::InvalidStateTransition);
+        self.state = next;
+        Ok(())
+    }
+}
+
+// Cluster-specific program and account addresses, set once by initialize_config so one
+// build serves devnet, testnet and mainnet. Each has an admin setter for later changes.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DeploymentParams {
+    pub graduation_dex_program: Pubkey,
+    pub dlmm_program: Pubkey,
+    pub buyback_dex_program: Pubkey,
+    pub profile_program: Pubkey,
+    pub name_service_program: Pubkey,
+    pub sol_tld_authority: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LaunchParams {
+    pub name: String,
+    pub symbol: String,
+    pub initial_supply: u64,
+    pub curve_ratio: u64,
+    pub max_supply: u64,
+    // Metadata fixed at creation, in the launch and in Metaplex, with no governance override
+    pub immutable_metadata: bool,
+    // Metadata URI and its content hash, committed at creation; required for immutable
+    // launches, which can never set one later
+    pub metadata_uri: String,
+    pub metadata_hash: [u8; 32],
+    // Market cap in lamports at which the launch may graduate early; zero waits for max supply
+    pub graduation_market_cap: u64,
+    // What happens to curve supply still unsold at graduation
+    pub residual_policy: ResidualPolicy,
+    // Linear release period for CreatorVesting, counted from graduation
+    pub residual_vesting_secs: i64,
+}
+
+impl LaunchParams {
+    // Every rule a new launch must pass, shared by launch creation and the dry run
+    fn validate(&self, config: &Config) -> Result<()> {
+        require!(
+            !self.name.is_empty() && self.name.len() <= MAX_NAME_LEN,
+            LaunchError::InvalidName
+        );
+        require!(
+            !self.symbol.is_empty() && self.symbol.len() <= MAX_SYMBOL_LEN,
+            LaunchError::InvalidSymbol
+        );
+        require!(!config.is_reserved_symbol(&self.symbol), LaunchError::ReservedSymbol);
+        require!(self.metadata_uri.len() <= MAX_URI_LEN, LaunchError::UriTooLong);
+        require!(
+            !self.immutable_metadata || !self.metadata_uri.is_empty(),
+            LaunchError::MetadataUriRequired
+        );
+        require!(self.curve_ratio > 0, LaunchError::InvalidCurveRatio);
+        // The curve must have supply left to sell, within the protocol's caps
+        require!(self.max_supply > self.initial_supply, LaunchError::InvalidMaxSupply);
+        require!(
+            config.max_total_supply == 0 || self.max_supply <= config.max_total_supply,
+            LaunchError::InvalidMaxSupply
+        );
+        require!(
+            config.max_initial_supply == 0 || self.initial_supply <= config.max_initial_supply,
+            LaunchError::InitialSupplyTooLarge
+        );
+        require!(
+            config.max_initial_supply_bps == 0
+                || self.initial_supply <= bps_of(self.max_supply, config.max_initial_supply_bps)?,
+            LaunchError::InitialSupplyTooLarge
+        );
+        require!(
+            self.graduation_market_cap == 0
+                || (self.graduation_market_cap >= config.min_graduation_market_cap
+                    && self.graduation_market_cap <= config.max_graduation_market_cap),
+            LaunchError::InvalidGraduationTarget
+        );
+        let vests = self.residual_policy == ResidualPolicy::CreatorVesting;
+        require!(vests == (self.residual_vesting_secs > 0), LaunchError::InvalidResidualPolicy);
+
+        // The curve must be able to price its last token without overflowing
+        calculate_price(
+            self.max_supply,
+            10u64.pow(TOKEN_DECIMALS as u32),
+            self.curve_ratio,
+            TOKEN_DECIMALS,
+            Rounding::Up,
+        )
+        .ok_or(LaunchError::InvalidPriceCalculation)?;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LaunchState {
+    // Gated by a crowdfund, reservations or an open time; the curve only takes allocations
+    #[default]
+    Pending,
+    Trading,
+    CurveComplete,
+    Graduating,
+    Graduated,
+    // A failed crowdfund is refunding its pledges
+    Refunding,
+    Closed,
+    // Aborted before the curve completed; holders reclaim their cost basis
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CurveType {
+    #[default]
+    Linear,
+    VirtualReserves,
+}
+
+// DEX a launch's graduation liquidity is seeded into. The pool instruction is caller-built
+// either way; the target only decides which program finalize_graduation accepts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GraduationVenue {
+    // Pool created through the config's graduation DEX program
+    #[default]
+    Pool,
+    // Meteora DLMM position, concentrating the liquidity in bins around the final price
+    Dlmm,
+}
+
+// Fate of the curve supply left unsold when a launch graduates, fixed at creation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResidualPolicy {
+    // Never minted, so total supply stays at what the curve sold
+    #[default]
+    Burn,
+    // Paired into the graduation liquidity with SOL at the final price, as far as the
+    // remaining reserves allow; the unpaired rest is burned
+    AddToLiquidity,
+    // Minted to the launch's community vault
+    CommunityVault,
+    // Minted to an escrow the creator draws down linearly after graduation
+    CreatorVesting,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CreatorIdentityKind {
+    #[default]
+    None,
+    // SPL Name Service record under the .sol TLD
+    SolDomain,
+    // `[b"profile", creator]` PDA of Config::profile_program
+    Profile,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TradeDirection {
+    Buy,
+    Sell,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PayoutMode {
+    Native,
+    Wsol,
+}
+
+// Returned from every trade so clients and CPI callers get exact outcomes. Anchor writes it
+// as return data, so later instructions in the same bundle can read the post-trade state
+// with get_return_data instead of re-reading the launch account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TradeResult {
+    pub amount: u64,
+    // Lamports charged on buys or paid out on sells, fees included
+    pub lamports: u64,
+    pub fee: u64,
+    pub spot_price: u64,
+    pub total_supply: u64,
+    // Tokens the curve can still sell before it completes
+    pub remaining_supply: u64,
+}
+
+// Result of get_collateral_value, in lamports
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CollateralValue {
+    pub amount: u64,
+    // Amount times the current spot price, ignoring impact and fees
+    pub spot_value: u64,
+    // Lamports the position would pay out if sold into the curve now
+    pub value: u64,
+    // Discount of value against spot_value
+    pub haircut_bps: u16,
+}
+
+// Both legs of swap_between_launches, returned the same way as a single trade's result
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SwapResult {
+    pub sold: TradeResult,
+    pub bought: TradeResult,
+}
+
+// Instruction data sent to a launch's hook program after every trade
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TradeHookArgs {
+    pub trader: Pubkey,
+    pub is_buy: bool,
+    pub result: TradeResult,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct GraduationResult {
+    pub total_supply: u64,
+    pub reserves: u64,
+    pub final_price: u64,
+    pub graduated_at: i64,
+    // Lamports paid to this caller; zero when the launch had already graduated
+    pub bounty: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct CurveStage {
+    // Supply at which this stage's multiplier starts applying
+    pub supply_threshold: u64,
+    // Multiplier applied to the curve ratio, in bps (15_000 = 1.5x)
+    pub multiplier_bps: u16,
+}
+
+impl CurveStage {
+    const LEN: usize = 8 + // supply_threshold
+        2; // multiplier_bps
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct FeeRecipient {
+    pub wallet: Pubkey,
+    pub bps: u16,
+}
+
+impl FeeRecipient {
+    const LEN: usize = 32 + // wallet
+        2; // bps
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct FeeTier {
+    // Market cap in lamports at which this tier's discount applies
+    pub market_cap_threshold: u64,
+    pub discount_bps: u16,
+}
+
+impl FeeTier {
+    const LEN: usize = 8 + // market_cap_threshold
+        2; // discount_bps
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RewardTier {
+    // Lifetime launch volume in lamports that unlocks this tier
+    pub volume_threshold: u64,
+    pub lamports: u64,
+    pub tokens: u64,
+}
+
+impl RewardTier {
+    const LEN: usize = 8 + // volume_threshold
+        8 + // lamports
+        8; // tokens
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct Milestone {
+    // Seconds after graduation at which this tranche unlocks
+    pub unlock_after_secs: i64,
+    pub bps: u16,
+}
+
+impl Milestone {
+    const LEN: usize = 8 + // unlock_after_secs
+        2; // bps
+}
+
+#[account]
+#[derive(Default)]
+pub struct LaunchStats {
+    pub launch: Pubkey,
+    pub trade_count: u64,
+    pub total_volume: u64,
+    pub window_start: i64,
+    pub window_volume: u64,
+    pub reward_tiers_claimed: u8,
+    pub breaker_window_start: i64,
+    pub breaker_peak_price: u64,
+    pub sells_paused_until: i64,
+    pub bump: u8,
+}
+
+impl LaunchStats {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        8 + // trade_count
+        8 + // total_volume
+        8 + // window_start
+        8 + // window_volume
+        1 + // reward_tiers_claimed
+        8 + // breaker_window_start
+        8 + // breaker_peak_price
+        8 + // sells_paused_until
+        1; // bump
+
+    // Halve the windowed volume for every full window elapsed so the dynamic fee decays back
+    fn roll_window(&mut self, now: i64, window_secs: i64) {
+        if window_secs <= 0 {
+            return;
+        }
+
+        let elapsed = now.saturating_sub(self.window_start) / window_secs;
+        if elapsed > 0 {
+            self.window_volume = if elapsed >= 64 {
+                0
+            } else {
+                self.window_volume >> elapsed
+            };
+            self.window_start = now;
+        }
+    }
+
+    fn record_trade(&mut self, lamports: u64) {
+        self.trade_count = self.trade_count.saturating_add(1);
+        self.total_volume = self.total_volume.saturating_add(lamports);
+        self.window_volume = self.window_volume.saturating_add(lamports);
+    }
+
+    // Track the peak spot price over the launch's breaker window and pause sells once the
+    // price falls more than the configured drop below it
+    fn track_price(&mut self, launch: &Launch, spot_price: u64, now: i64) -> Result<()> {
+        if launch.breaker_drop_bps == 0 {
+            return Ok(());
+        }
+        if now >= self.breaker_window_start.saturating_add(launch.breaker_window_secs) {
+            self.breaker_window_start = now;
+            self.breaker_peak_price = spot_price;
+        }
+        self.breaker_peak_price = self.breaker_peak_price.max(spot_price);
+
+        let drop = bps_of(self.breaker_peak_price, launch.breaker_drop_bps)?;
+        if spot_price < self.breaker_peak_price - drop {
+            self.sells_paused_until = now.saturating_add(launch.breaker_pause_secs);
+            // Measure the next window from the crashed price rather than re-tripping forever
+            self.breaker_window_start = now;
+            self.breaker_peak_price = spot_price;
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Snapshot {
+    pub launch: Pubkey,
+    pub index: u32,
+    pub merkle_root: [u8; 32],
+    pub slot: u64,
+    pub committed_by: Pubkey,
+    pub bump: u8,
+}
+
+impl Snapshot {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        4 + // index
+        32 + // merkle_root
+        8 + // slot
+        32 + // committed_by
+        1; // bump
+}
+
+#[account]
+pub struct TokenomicsRevision {
+    pub launch: Pubkey,
+    pub version: u32,
+    pub document_hash: [u8; 32],
+    pub previous_hash: [u8; 32],
+    pub committed_at: i64,
+    pub bump: u8,
+}
+
+impl TokenomicsRevision {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        4 + // version
+        32 + // document_hash
+        32 + // previous_hash
+        8 + // committed_at
+        1; // bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum ProposalAction {
+    SetFeeDestination { destination: Pubkey },
+    ApproveMetadataChange { content_hash: [u8; 32] },
+}
+
+impl ProposalAction {
+    const LEN: usize = 1 + // variant
+        32; // largest payload
+}
+
+#[account]
+pub struct Proposal {
+    pub launch: Pubkey,
+    pub index: u32,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub voting_ends_at: i64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl Proposal {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        4 + // index
+        32 + // proposer
+        ProposalAction::LEN + // action
+        8 + // yes_votes
+        8 + // no_votes
+        8 + // voting_ends_at
+        1 + // executed
+        1; // bump
+}
+
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub support: bool,
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    const LEN: usize = 8 + // discriminator
+        32 + // proposal
+        32 + // voter
+        8 + // amount
+        1 + // support
+        1; // bump
+}
+
+#[account]
+#[derive(Default)]
+pub struct BuyerState {
+    pub launch: Pubkey,
+    pub buyer: Pubkey,
+    pub pledged_lamports: u64,
+    pub pledged_tokens: u64,
+    pub bump: u8,
+    // Curve-tracked balance and the balance-seconds it has accrued
+    pub loyalty_balance: u64,
+    pub loyalty_points: u128,
+    pub loyalty_updated_at: i64,
+    pub loyalty_claimed: bool,
+    // Early-supporter number, one-based; zero if the buyer was not among the first
+    pub supporter_number: u16,
+    pub fair_mint_lamports: u64,
+    // Lamports paid into reserves for the curve-bought tokens still tracked, for refunds
+    pub cost_basis: u64,
+    pub basis_tokens: u64,
+    // Lamport volume traded during `competition_epoch`
+    pub competition_epoch: u32,
+    pub competition_volume: u64,
+    // LP claim units from early buys, and the LP rewards already paid against them
+    pub lp_units: u64,
+    pub lp_rewards_claimed: u64,
+    // Wallet-set daily buy cap in lamports (zero is uncapped), a raise waiting out its delay,
+    // and the tally for the current day
+    pub daily_spend_cap: u64,
+    pub pending_spend_cap: u64,
+    pub pending_spend_cap_at: i64,
+    pub spend_day: i64,
+    pub spent_today: u64,
+    // Ring of the last buy_tokens idempotency nonces; zero marks an empty slot
+    pub recent_nonces: [u64; RECENT_NONCES],
+    pub next_nonce_slot: u8,
+}
+
+impl BuyerState {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        32 + // buyer
+        8 + // pledged_lamports
+        8 + // pledged_tokens
+        1 + // bump
+        8 + // loyalty_balance
+        16 + // loyalty_points
+        8 + // loyalty_updated_at
+        1 + // loyalty_claimed
+        2 + // supporter_number
+        8 + // fair_mint_lamports
+        8 + // cost_basis
+        8 + // basis_tokens
+        4 + // competition_epoch
+        8 + // competition_volume
+        8 + // lp_units
+        8 + // lp_rewards_claimed
+        8 + // daily_spend_cap
+        8 + // pending_spend_cap
+        8 + // pending_spend_cap_at
+        8 + // spend_day
+        8 + // spent_today
+        8 * RECENT_NONCES + // recent_nonces
+        1; // next_nonce_slot
+
+    fn apply_pending_spend_cap(&mut self, now: i64) {
+        if self.pending_spend_cap_at != 0 && now >= self.pending_spend_cap_at {
+            self.daily_spend_cap = self.pending_spend_cap;
+            self.pending_spend_cap = 0;
+            self.pending_spend_cap_at = 0;
+        }
+    }
+
+    // Overwrite the oldest remembered nonce
+    fn remember_nonce(&mut self, nonce: u64) {
+        self.recent_nonces[self.next_nonce_slot as usize] = nonce;
+        self.next_nonce_slot = ((self.next_nonce_slot as usize + 1) % RECENT_NONCES) as u8;
+    }
+
+    // Count a buy against the daily cap, starting a fresh tally when the day rolls over
+    fn record_spend(&mut self, lamports: u64, now: i64) -> Result<()> {
+        self.apply_pending_spend_cap(now);
+        if self.daily_spend_cap == 0 {
+            return Ok(());
+        }
+        let day = now / SPEND_CAP_DAY_SECS;
+        if day != self.spend_day {
+            self.spend_day = day;
+            self.spent_today = 0;
+        }
+        let spent = self.spent_today.saturating_add(lamports);
+        require!(spent <= self.daily_spend_cap, LaunchError::SpendCapExceeded);
+        self.spent_today = spent;
+        Ok(())
+    }
+
+    fn accrue_loyalty(&mut self, until: i64) {
+        self.loyalty_points = self
+            .loyalty_points
+            .saturating_add(holding_points(self.loyalty_balance, self.loyalty_updated_at, until));
+        self.loyalty_updated_at = self.loyalty_updated_at.max(until);
+    }
+}
+
+#[account]
+#[derive(Default)]
+pub struct Raffle {
+    pub launch: Pubkey,
+    pub fee_bps: u16,
+    pub entries: [Pubkey; RAFFLE_ENTRIES],
+    pub entry_count: u8,
+    pub next_entry: u8,
+    pub randomness_account: Pubkey,
+    pub commit_slot: u64,
+    pub bump: u8,
+}
+
+impl Raffle {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        2 + // fee_bps
+        RAFFLE_ENTRIES * 32 + // entries
+        1 + // entry_count
+        1 + // next_entry
+        32 + // randomness_account
+        8 + // commit_slot
+        1; // bump
+
+    // Ring buffer of recent buyers; frozen while a draw is committed so entries can't be gamed
+    fn record_entry(&mut self, buyer: Pubkey) {
+        if self.randomness_account != Pubkey::default() {
+            return;
+        }
+
+        self.entries[self.next_entry as usize] = buyer;
+        self.next_entry = ((self.next_entry as usize + 1) % RAFFLE_ENTRIES) as u8;
+        self.entry_count = (self.entry_count as usize + 1).min(RAFFLE_ENTRIES) as u8;
+    }
+}
+
+#[account]
+pub struct Reservation {
+    pub launch: Pubkey,
+    pub index: u32,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub bump: u8,
+}
+
+impl Reservation {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        4 + // index
+        32 + // owner
+        8 + // lamports
+        1; // bump
+}
+
+// One trading-competition epoch and its committed leaderboard
+#[account]
+pub struct Competition {
+    pub epoch: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    // Merkle root of keccak(winner, lamports) leaves; zero until the epoch is closed
+    pub winners_root: [u8; 32],
+    pub prize_lamports: u64,
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl Competition {
+    const LEN: usize = 8 + // discriminator
+        4 + // epoch
+        8 + // starts_at
+        8 + // ends_at
+        32 + // winners_root
+        8 + // prize_lamports
+        8 + // claimed
+        1; // bump
+}
+
+// Marks a winner's competition prize as paid
+#[account]
+pub struct CompetitionClaim {
+    pub competition: Pubkey,
+    pub winner: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl CompetitionClaim {
+    const LEN: usize = 8 + // discriminator
+        32 + // competition
+        32 + // winner
+        8 + // amount
+        1; // bump
+}
+
+#[account]
+pub struct AirdropClaim {
+    pub launch: Pubkey,
+    pub claimant: Pubkey,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl AirdropClaim {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        32 + // claimant
+        8 + // amount
+        1; // bump
+}
+
+#[account]
+pub struct BlacklistEntry {
+    pub wallet: Pubkey,
+    pub added_at: i64,
+    pub bump: u8,
+}
+
+impl BlacklistEntry {
+    const LEN: usize = 8 + // discriminator
+        32 + // wallet
+        8 + // added_at
+        1; // bump
+}
+
+#[account]
+pub struct MarketMakerEntry {
+    pub wallet: Pubkey,
+    pub max_supply_delta_bps: u16,
+    pub bump: u8,
+}
+
+impl MarketMakerEntry {
+    const LEN: usize = 8 + // discriminator
+        32 + // wallet
+        2 + // max_supply_delta_bps
+        1; // bump
+}
+
+// Creator fee recipients of one launch; collects proceeds as the launch's fee destination
+#[account]
+pub struct FeeSplit {
+    pub launch: Pubkey,
+    pub recipient_count: u8,
+    pub recipients: [FeeRecipient; MAX_FEE_RECIPIENTS],
+    pub bump: u8,
+}
+
+impl FeeSplit {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        1 + // recipient_count
+        MAX_FEE_RECIPIENTS * FeeRecipient::LEN + // recipients
+        1; // bump
+}
+
+#[account]
+pub struct RewardSchedule {
+    pub tier_count: u8,
+    pub tiers: [RewardTier; MAX_REWARD_TIERS],
+    pub bump: u8,
+}
+
+impl RewardSchedule {
+    const LEN: usize = 8 + // discriminator
+        1 + // tier_count
+        MAX_REWARD_TIERS * RewardTier::LEN + // tiers
+        1; // bump
+}
+
+#[account]
+pub struct TradeReceipt {
+    pub buyer: Pubkey,
+    pub launch: Pubkey,
+    pub seq: u64,
+    pub amount: u64,
+    pub price: u64,
+    pub slot: u64,
+    pub bump: u8,
+}
+
+impl TradeReceipt {
+    const LEN: usize = 8 + // discriminator
+        32 + // buyer
+        32 + // launch
+        8 + // seq
+        8 + // amount
+        8 + // price
+        8 + // slot
+        1; // bump
+}
+
+// Numbered badge held by one of a launch's first unique buyers
+#[account]
+pub struct EarlySupporterBadge {
+    pub launch: Pubkey,
+    pub holder: Pubkey,
+    pub number: u16,
+    pub bump: u8,
+}
+
+impl EarlySupporterBadge {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        32 + // holder
+        2 + // number
+        1; // bump
+}
+
+#[account]
+pub struct OtcOffer {
+    pub launch: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub ask_lamports: u64,
+    pub bump: u8,
+}
+
+impl OtcOffer {
+    const LEN: usize = 8 + // discriminator
+        32 + // launch
+        32 + // seller
+        8 + // amount
+        8 + // ask_lamports
+        1; // bump
+}
+
+#[account]
+pub struct HookAllowlistEntry {
+    pub hook_program: Pubkey,
+    pub bump: u8,
+}
+
+impl HookAllowlistEntry {
+    const LEN: usize = 8 + // discriminator
+        32 + // hook_program
+        1; // bump
+}
+
+#[account]
+pub struct SessionAuthority {
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub spend_cap: u64,
+    pub spent: u64,
+    pub bump: u8,
+}
+
+impl SessionAuthority {
+    const LEN: usize = 8 + // discriminator
+        32 + // owner
+        32 + // session_key
+        8 + // expires_at
+        8 + // spend_cap
+        8 + // spent
+        1; // bump
+}
+
+// Error codes are assigned in declaration order from 6000 and clients map on them, so new
+// variants are only ever appended
+#[error_code]
+pub enum LaunchError {
+    #[msg("Launch is not active")]
+    LaunchInactive,
+    #[msg("Invalid price calculation")]
+    InvalidPriceCalculation,
+    #[msg("Max supply must be at least the initial supply")]
+    InvalidMaxSupply,
+    #[msg("Purchase would exceed the launch max supply")]
+    MaxSupplyExceeded,
+    #[msg("Bonding curve is complete")]
+    CurveComplete,
+    #[msg("Session expiry must be in the future")]
+    InvalidSessionExpiry,
+    #[msg("Session spend cap must be greater than zero")]
+    InvalidSpendCap,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Session spend cap exceeded")]
+    SessionSpendCapExceeded,
+    #[msg("Fee basis points exceed 10000")]
+    InvalidFeeBps,
+    #[msg("Vault has no proceeds to distribute")]
+    NothingToDistribute,
+    #[msg("Account is not an empty protocol-owned token account")]
+    InvalidSweepAccount,
+    #[msg("Nothing to sweep")]
+    NothingToSweep,
+    #[msg("Dynamic fee window and velocity threshold must be positive")]
+    InvalidDynamicFeeParams,
+    #[msg("Milestones must be ordered, non-zero and sum to 10000 bps")]
+    InvalidMilestones,
+    #[msg("Milestones can only be set once, before trading starts")]
+    MilestonesLocked,
+    #[msg("Payout stream is not configured or conflicts with milestones")]
+    InvalidPayoutStream,
+    #[msg("Bonding curve is not complete")]
+    CurveNotComplete,
+    #[msg("Launch has already graduated")]
+    AlreadyGraduated,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Snapshot slot cannot be in the future")]
+    InvalidSnapshotSlot,
+    #[msg("Launch has not graduated")]
+    NotGraduated,
+    #[msg("Signer does not hold launch tokens")]
+    NotATokenHolder,
+    #[msg("Invalid proposal")]
+    InvalidProposal,
+    #[msg("Vote amount must be greater than zero")]
+    InvalidVoteAmount,
+    #[msg("Voting period has ended")]
+    VotingClosed,
+    #[msg("Voting period is still open")]
+    VotingOpen,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Proposal did not pass")]
+    ProposalRejected,
+    #[msg("Crowdfund can only be configured once, before trading starts")]
+    CrowdfundLocked,
+    #[msg("Crowdfund target must be positive and deadline in the future")]
+    InvalidCrowdfundParams,
+    #[msg("Launch is waiting for its crowdfund target")]
+    CrowdfundPending,
+    #[msg("Launch has no pending crowdfund")]
+    CrowdfundNotPending,
+    #[msg("Crowdfund deadline has passed")]
+    CrowdfundExpired,
+    #[msg("Crowdfund has not failed")]
+    CrowdfundNotFailed,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Metadata URI is too long")]
+    UriTooLong,
+    #[msg("Metadata content hash does not match the committed hash")]
+    MetadataHashMismatch,
+    #[msg("Donation is too small")]
+    InvalidDonation,
+    #[msg("Memo is too long")]
+    MemoTooLong,
+    #[msg("Buyer must hold a valid attestation token")]
+    AttestationRequired,
+    #[msg("Raffle account is required for this launch")]
+    RaffleAccountRequired,
+    #[msg("Raffle has no entries")]
+    NoRaffleEntries,
+    #[msg("A raffle draw is already pending")]
+    RaffleDrawPending,
+    #[msg("Invalid randomness account")]
+    InvalidRandomness,
+    #[msg("Randomness has not been revealed yet")]
+    RandomnessNotResolved,
+    #[msg("Winner does not match the drawn entry")]
+    InvalidRaffleWinner,
+    #[msg("Trade price moved beyond the slippage limit")]
+    SlippageExceeded,
+    #[msg("Curve reserves cannot cover this sell")]
+    InsufficientReserves,
+    #[msg("Trading has already started")]
+    TradingAlreadyStarted,
+    #[msg("Max wallet bps must be between 1 and 10000")]
+    InvalidMaxWallet,
+    #[msg("Purchase would exceed the max wallet holding")]
+    MaxWalletExceeded,
+    #[msg("Invalid circuit breaker parameters")]
+    InvalidCircuitBreaker,
+    #[msg("Sells are paused by the circuit breaker")]
+    SellsPaused,
+    #[msg("Trading has not started")]
+    TradingNotStarted,
+    #[msg("Reservations must be settled before public trading")]
+    ReservationsPending,
+    #[msg("Trading open time must be in the future")]
+    InvalidReservationWindow,
+    #[msg("Reservations are closed")]
+    ReservationsClosed,
+    #[msg("Reservation is below the base token price")]
+    InvalidReservationAmount,
+    #[msg("Reservation accounts are invalid or out of order")]
+    InvalidReservation,
+    #[msg("Curve stages must have increasing thresholds within supply and multipliers above 1x")]
+    InvalidCurveStages,
+    #[msg("Timelock cannot be negative")]
+    InvalidTimelock,
+    #[msg("Withdrawal has not been requested or is still timelocked")]
+    WithdrawalTimelocked,
+    #[msg("Invalid relaunch")]
+    InvalidRelaunch,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Wallet is blacklisted")]
+    Blacklisted,
+    #[msg("Transaction compute-unit price is below the graduation minimum")]
+    GraduationBidTooLow,
+    #[msg("Launch batch is empty, too large, or has invalid accounts")]
+    InvalidBatch,
+    #[msg("Trade is below the configured minimum size")]
+    TradeTooSmall,
+    #[msg("Reward tiers must have strictly increasing volume thresholds")]
+    InvalidRewardSchedule,
+    #[msg("No new reward tier has been reached")]
+    NoRewardAvailable,
+    #[msg("Rewards pool cannot cover this claim")]
+    RewardsPoolDepleted,
+    #[msg("Flat sale must start between the initial and max supply")]
+    InvalidFlatSale,
+    #[msg("OTC offer amount and ask must be non-zero")]
+    InvalidOtcOffer,
+    #[msg("Token account is not the owner's associated token account")]
+    InvalidTokenAccount,
+    #[msg("Virtual reserves must be non-zero and exceed the curve's sellable supply")]
+    InvalidVirtualReserves,
+    #[msg("Hook program is missing or not allow-listed")]
+    HookNotAllowed,
+    #[msg("Trade tree is missing or does not belong to the launch")]
+    InvalidTradeTree,
+    #[msg("Launch cannot move to that state from its current one")]
+    InvalidStateTransition,
+    #[msg("Signer is not the proposed admin")]
+    NotPendingAdmin,
+    #[msg("Admin handover is still timelocked")]
+    AdminTransferTimelocked,
+    #[msg("wSOL payout needs the trader's native-mint token account")]
+    WsolAccountRequired,
+    #[msg("Launch deed has already been minted")]
+    DeedAlreadyMinted,
+    #[msg("Signer does not hold the launch deed")]
+    NotDeedHolder,
+    #[msg("Trade moves supply further than allowed in one slot")]
+    SupplyDeltaExceeded,
+    #[msg("Transaction is missing a recent freshness proof")]
+    StaleTransaction,
+    #[msg("Airdrop claim window has closed")]
+    AirdropExpired,
+    #[msg("Airdrop claim window is still open")]
+    AirdropNotExpired,
+    #[msg("Name must be 1 to 32 bytes")]
+    InvalidName,
+    #[msg("Symbol must be 1 to 10 bytes")]
+    InvalidSymbol,
+    #[msg("Curve ratio must be non-zero")]
+    InvalidCurveRatio,
+    #[msg("Launch metadata is immutable")]
+    MetadataImmutable,
+    #[msg("Trade receipts are only issued for buys")]
+    InvalidTradeReceipt,
+    #[msg("Graduation market cap is outside the configured bounds")]
+    InvalidGraduationTarget,
+    #[msg("High-security launch requires the secondary authority's signature")]
+    MissingCosigner,
+    #[msg("Account does not match the one recorded on-chain")]
+    AccountMismatch,
+    #[msg("Launch is already mid-trade")]
+    ReentrantTrade,
+    #[msg("Bootstrap deposits need a virtual-reserves curve and are refunded to the creator")]
+    InvalidBootstrapDeposit,
+    #[msg("Loyalty reward round is not open or has no accrued points")]
+    InvalidLoyaltyRound,
+    #[msg("Symbol is reserved")]
+    ReservedSymbol,
+    #[msg("Buyer is not one of the launch's early supporters")]
+    NotEarlySupporter,
+    #[msg("Token account is frozen")]
+    AccountFrozen,
+    #[msg("Token account has a delegate set")]
+    UnexpectedDelegate,
+    #[msg("Cold wallet is already registered")]
+    ColdWalletAlreadySet,
+    #[msg("A swap must sell and buy different launches")]
+    SwapSameLaunch,
+    #[msg("Fee tiers must have increasing market cap thresholds and discounts")]
+    InvalidFeeTiers,
+    #[msg("Launch metadata is frozen by the admin")]
+    MetadataFrozen,
+    #[msg("Holder gate needs a positive minimum balance")]
+    InvalidHolderGate,
+    #[msg("Buyer does not hold enough of the launch's gate mint")]
+    HolderGateNotMet,
+    #[msg("Invalid fair mint parameters or state")]
+    InvalidFairMint,
+    #[msg("Fair mint has not been finalized")]
+    FairMintPending,
+    #[msg("Fair mint deposit window is closed")]
+    FairMintClosed,
+    #[msg("Mint supply exceeds what the launch accounts for")]
+    SupplyMismatch,
+    #[msg("Unknown operator permission bits")]
+    InvalidOperatorPermissions,
+    #[msg("A memo needs the SPL Memo program account")]
+    MemoProgramRequired,
+    #[msg("Launch has unclaimed pledges or an open fair mint")]
+    CancelBlocked,
+    #[msg("Launch has not been cancelled")]
+    LaunchNotCancelled,
+    #[msg("Identity account is not owned by the launch creator")]
+    InvalidCreatorIdentity,
+    #[msg("Graduation seeds liquidity; use begin_graduation")]
+    ChunkedGraduationRequired,
+    #[msg("Graduation has not been started")]
+    GraduationNotStarted,
+    #[msg("Graduation liquidity has not been fully deposited")]
+    LiquidityNotDeposited,
+    #[msg("Initial supply exceeds the protocol cap")]
+    InitialSupplyTooLarge,
+    #[msg("Competition must end in the future")]
+    InvalidCompetition,
+    #[msg("A trading competition is still running")]
+    CompetitionActive,
+    #[msg("Competition results were already committed")]
+    CompetitionSettled,
+    #[msg("Competition results have not been committed")]
+    CompetitionNotSettled,
+    #[msg("Buy exceeds the wallet's daily spend cap")]
+    SpendCapExceeded,
+    #[msg("Fee split needs 1-5 recipients whose weights sum to 10000 bps")]
+    InvalidFeeSplit,
+    #[msg("Idempotency nonces must be non-zero and come with the buyer state")]
+    InvalidNonce,
+    #[msg("Only the creator vesting policy takes a vesting period, and it requires one")]
+    InvalidResidualPolicy,
+    #[msg("Residual token account does not belong to the launch's residual policy")]
+    InvalidResidualDestination,
+    #[msg("The trader's buyer state exists and must be passed")]
+    BuyerStateRequired,
+    #[msg("The protocol has no program configured for this graduation venue")]
+    GraduationVenueUnavailable,
+    #[msg("Immutable launches must set their metadata URI at creation")]
+    MetadataUriRequired,
+}
+
+fn process_buy(
+    ctx: Context<Trade>,
+    amount: u64,
+    max_cost: u64,
+    memo: Option<String>,
+) -> Result<TradeResult> {
+    require_buyer_state(&ctx.accounts.buyer_state, &ctx.accounts.buyer_state_address)?;
+    let accounts = &mut *ctx.accounts;
+    execute_buy(
+        BuyAccounts {
+            program_id: ctx.program_id,
+            launch: &mut accounts.launch,
+            buyer: accounts.trader.to_account_info(),
+            payer: accounts.trader.to_account_info(),
+            payer_seeds: &[],
+            blacklist_entry: &accounts.blacklist_entry,
+            market_maker_entry: accounts.market_maker_entry.as_deref(),
+            vault: &accounts.vault,
+            stats: &mut accounts.stats,
+            config: &accounts.config,
+            frontend_fee_recipient: accounts.frontend_fee_recipient.as_ref(),
+            mint: &accounts.mint,
+            mint_authority: &accounts.mint_authority,
+            mint_authority_bump: ctx.bumps.mint_authority,
+            token_account: &accounts.trader_token_account,
+            rent_payer: &accounts.rent_payer,
+            attestation_token_account: accounts.attestation_token_account.as_deref(),
+            gate_token_account: accounts.gate_token_account.as_deref(),
+            raffle: accounts.raffle.as_mut(),
+            instructions: accounts.instructions.as_ref(),
+            hook_program: accounts.hook_program.as_ref(),
+            hook_allowlist_entry: accounts.hook_allowlist_entry.as_deref(),
+            trade_tree: accounts.trade_tree.as_ref(),
+            tree_authority: accounts.tree_authority.as_ref(),
+            tree_authority_bump: ctx.bumps.tree_authority,
+            compression_program: accounts.compression_program.as_ref(),
+            noop_program: accounts.noop_program.as_ref(),
+            memo_program: accounts.memo_program.as_ref(),
+            trade_receipt: accounts
+                .trade_receipt
+                .as_mut()
+                .map(|receipt| (receipt, ctx.bumps.trade_receipt.unwrap_or_default())),
+            buyer_state: accounts
+                .buyer_state
+                .as_mut()
+                .map(|holder| (holder, ctx.bumps.buyer_state.unwrap_or_default())),
+            system_program: &accounts.system_program,
+            token_program: &accounts.token_program,
+            associated_token_program: &accounts.associated_token_program,
+        },
+        ctx.remaining_accounts,
+        amount,
+        max_cost,
+        memo,
+    )
+}
+
+// Everything a buy reads or writes, so direct trades and session-key buys share one core.
+// The buyer receives the tokens and is credited with the trade; the payer funds it, signing
+// with `payer_seeds` when it is a program-derived escrow.
+struct BuyAccounts<'a, 'info> {
+    program_id: &'a Pubkey,
+    launch: &'a mut Account<'info, Launch>,
+    buyer: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    payer_seeds: &'a [&'a [&'a [u8]]],
+    blacklist_entry: &'a UncheckedAccount<'info>,
+    market_maker_entry: Option<&'a MarketMakerEntry>,
+    vault: &'a SystemAccount<'info>,
+    stats: &'a mut Account<'info, LaunchStats>,
+    config: &'a Account<'info, Config>,
+    frontend_fee_recipient: Option<&'a UncheckedAccount<'info>>,
+    mint: &'a Account<'info, Mint>,
+    mint_authority: &'a UncheckedAccount<'info>,
+    mint_authority_bump: u8,
+    token_account: &'a UncheckedAccount<'info>,
+    rent_payer: &'a Signer<'info>,
+    attestation_token_account: Option<&'a TokenAccount>,
+    gate_token_account: Option<&'a TokenAccount>,
+    raffle: Option<&'a mut Account<'info, Raffle>>,
+    instructions: Option<&'a UncheckedAccount<'info>>,
+    hook_program: Option<&'a UncheckedAccount<'info>>,
+    hook_allowlist_entry: Option<&'a HookAllowlistEntry>,
+    trade_tree: Option<&'a UncheckedAccount<'info>>,
+    tree_authority: Option<&'a UncheckedAccount<'info>>,
+    tree_authority_bump: Option<u8>,
+    compression_program: Option<&'a Program<'info, SplAccountCompression>>,
+    noop_program: Option<&'a Program<'info, Noop>>,
+    memo_program: Option<&'a UncheckedAccount<'info>>,
+    trade_receipt: Option<(&'a mut Account<'info, TradeReceipt>, u8)>,
+    buyer_state: Option<(&'a mut Account<'info, BuyerState>, u8)>,
+    system_program: &'a Program<'info, System>,
+    token_program: &'a Program<'info, Token>,
+    associated_token_program: &'a Program<'info, AssociatedToken>,
+}
+
+fn execute_buy(
+    accounts: BuyAccounts,
+    remaining_accounts: &[AccountInfo],
+    amount: u64,
+    max_cost: u64,
+    memo: Option<String>,
+) -> Result<TradeResult> {
+    let BuyAccounts {
+        program_id,
+        launch,
+        buyer,
+        payer,
+        payer_seeds,
+        stats,
+        config,
+        mut raffle,
+        trade_receipt,
+        buyer_state,
+        ..
+    } = accounts;
+    if let Some(memo) = memo.as_ref() {
+        require!(memo.len() <= MAX_MEMO_LEN, LaunchError::MemoTooLong);
+        require!(accounts.memo_program.is_some(), LaunchError::MemoProgramRequired);
+    }
+    require_not_blacklisted(accounts.blacklist_entry)?;
+    require_fresh_tx(launch, accounts.instructions)?;
+    config.verify_attestation(accounts.attestation_token_account, buyer.key)?;
+    launch.verify_holder_gate(accounts.gate_token_account, buyer.key)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(!launch.trade_in_flight, LaunchError::ReentrantTrade);
+    launch.check_mint_supply(accounts.mint)?;
+    let (new_supply, price) = launch.quote_buy(amount, now)?;
+
+    config.check_trade_size(amount, price)?;
+    let market_maker = launch.market_maker(
+        accounts.market_maker_entry,
+        config.market_maker_window_secs,
+        now,
+    );
+    launch.check_supply_delta(
+        new_supply,
+        market_maker.map_or(config.max_supply_delta_bps, |entry| entry.max_supply_delta_bps),
+    )?;
+    stats.roll_window(now, config.velocity_window_secs);
+    let discount_bps = config.holder_discount_bps(buyer.key, remaining_accounts)?;
+    let fee = fee_of(price, config.trade_fee_bps(launch, true, stats, discount_bps)?)?;
+    let raffle_share =
+        launch.enter_raffle(raffle.as_deref_mut().map(|raffle| &mut **raffle), buyer.key(), fee)?;
+    let (rebate_tokens, rebate_lamports) = launch.quote_rebate(fee - raffle_share, new_supply)?;
+    let cost = price
+        .checked_add(fee)
+        .ok_or(LaunchError::InvalidPriceCalculation)?;
+    require!(cost <= max_cost, LaunchError::SlippageExceeded);
+
+    // Create the buyer's token account up front so its balance is known before any state change
+    let trader_balance = ensure_token_account(
+        accounts.rent_payer,
+        accounts.token_account,
+        buyer.clone(),
+        accounts.mint,
+        accounts.system_program,
+        accounts.token_program,
+        accounts.associated_token_program,
+    )?;
+
+    // The treasury's cut of the fee stays in the vault until claimed
+    let frontend_share = config.frontend_share(
+        fee - raffle_share - rebate_lamports,
+        accounts.frontend_fee_recipient.is_some(),
+    )?;
+    let treasury_share = fee - raffle_share - rebate_lamports - frontend_share;
+    launch.record_buy(new_supply + rebate_tokens, price + rebate_lamports)?;
+    launch.rebate_emitted += rebate_tokens;
+    launch.accrued_protocol_fees += treasury_share;
+    if market_maker.is_none() {
+        launch.check_max_wallet(
+            trader_balance.saturating_add(amount + rebate_tokens),
+            now,
+        )?;
+    }
+    stats.record_trade(price);
+    stats.track_price(launch, launch.spot_price()?, now)?;
+    if let Some((holder, bump)) = buyer_state {
+        holder.launch = launch.key();
+        holder.buyer = buyer.key();
+        holder.bump = bump;
+        let balance = trader_balance.saturating_add(amount + rebate_tokens);
+        record_holder_buy(launch, config, holder, balance, price, new_supply - amount, now);
+        launch.add_cost_basis(holder, price + rebate_lamports, amount + rebate_tokens);
+        holder.record_spend(cost, now)?;
+    }
+
+    // Commit supply, reserves and stats, and persist them with the in-flight guard set, before
+    // any transfer or mint CPI. The guard stays set in account data until the instruction
+    // exits, so nothing invoked from here on (token programs, trade hooks) can re-enter.
+    launch.trade_in_flight = true;
+    launch.exit(program_id)?;
+    stats.exit(program_id)?;
+
+    // Transfer the raffle and routing frontend slices of the fee from the payer
+    let mut fee_splits = Vec::with_capacity(2);
+    if let Some(raffle) = raffle {
+        fee_splits.push((raffle.to_account_info(), raffle_share));
+    }
+    if let Some(frontend) = accounts.frontend_fee_recipient {
+        fee_splits.push((frontend.to_account_info(), frontend_share));
+    }
+    for (recipient, lamports) in fee_splits {
+        if lamports == 0 {
+            continue;
+        }
+        let fee_ix = anchor_lang::solana_program::system_instruction::transfer(
+            payer.key,
+            recipient.key,
+            lamports,
+        );
+        invoke_signed(
+            &fee_ix,
+            &[payer.clone(), recipient, accounts.system_program.to_account_info()],
+            payer_seeds,
+        )?;
+    }
+
+    // Transfer SOL from the payer to the launch vault, including the fee slices backing the
+    // rebate and the accrued treasury fee
+    let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+        payer.key,
+        &accounts.vault.key(),
+        price + rebate_lamports + treasury_share,
+    );
+    invoke_signed(
+        &transfer_ix,
+        &[
+            payer.clone(),
+            accounts.vault.to_account_info(),
+            accounts.system_program.to_account_info(),
+        ],
+        payer_seeds,
+    )?;
+
+    // Attach the memo in this instruction, signed by the trader so it attributes the deposit
+    if let (Some(memo), Some(memo_program)) = (memo, accounts.memo_program) {
+        invoke(
+            &Instruction {
+                program_id: MEMO_PROGRAM_ID,
+                accounts: vec![AccountMeta::new_readonly(buyer.key(), true)],
+                data: memo.into_bytes(),
+            },
+            &[buyer.clone(), memo_program.to_account_info()],
+        )?;
+    }
+
+    // Mint tokens to the buyer
+    let launch_key = launch.key();
+    let mint_authority: &[&[&[u8]]] =
+        &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[accounts.mint_authority_bump]]];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            token::MintTo {
+                mint: accounts.mint.to_account_info(),
+                to: accounts.token_account.to_account_info(),
+                authority: accounts.mint_authority.to_account_info(),
+            },
+            mint_authority,
+        ),
+        amount + rebate_tokens,
+    )?;
+
+    launch.trade_in_flight = false;
+
+    // Proof-of-purchase other programs can read, e.g. to gate on buying before a slot
+    if let Some((receipt, bump)) = trade_receipt {
+        receipt.buyer = buyer.key();
+        receipt.launch = launch_key;
+        receipt.seq = launch.trade_seq;
+        receipt.amount = amount;
+        receipt.price = price;
+        receipt.slot = Clock::get()?.slot;
+        receipt.bump = bump;
+    }
+
+    emit!(TradeEvent {
+        seq: launch.next_trade_seq(),
+        launch: launch_key,
+        trader: buyer.key(),
+        is_buy: true,
+        amount,
+        price,
+        fee,
+        lamports: cost,
+        limit: max_cost,
+        total_supply: launch.total_supply,
+        timestamp: now,
+    });
+
+    let result = TradeResult {
+        amount,
+        lamports: cost,
+        fee,
+        spot_price: launch.spot_price()?,
+        total_supply: launch.total_supply,
+        remaining_supply: launch.max_supply - launch.total_supply,
+    };
+    invoke_trade_hook(
+        launch,
+        accounts.hook_program,
+        accounts.hook_allowlist_entry,
+        buyer.clone(),
+        true,
+        result,
+    )?;
+    append_trade_record(
+        launch,
+        accounts.trade_tree,
+        accounts.tree_authority,
+        accounts.tree_authority_bump,
+        accounts.compression_program,
+        accounts.noop_program,
+        TradeHookArgs { trader: buyer.key(), is_buy: true, result },
+    )?;
+    Ok(result)
+}
+
+// Holder bookkeeping for a buy of `price` lamports starting at `prior_supply`: loyalty balance,
+// competition volume, LP units and the early-supporter number
+fn record_holder_buy(
+    launch: &mut Launch,
+    config: &Config,
+    holder: &mut BuyerState,
+    balance: u64,
+    price: u64,
+    prior_supply: u64,
+    now: i64,
+) {
+    launch.sync_loyalty(holder, balance, now);
+    config.record_competition_volume(holder, price, now);
+    if prior_supply < launch.lp_unit_cutoff_supply {
+        holder.lp_units = holder.lp_units.saturating_add(price);
+        launch.total_lp_units = launch.total_lp_units.saturating_add(price);
+    }
+    let supporter_slot_open = launch.early_supporter_count < launch.early_supporter_limit;
+    if holder.supporter_number == 0 && supporter_slot_open {
+        launch.early_supporter_count += 1;
+        holder.supporter_number = launch.early_supporter_count;
+    }
 }
 
-impl Launch {
-    const LEN: usize = 8 + // discriminator
-        32 + // creator
-        32 + // mint
-        32 + // name
-        8 + // symbol
-        8 + // initial_supply
-        8 + // total_supply
-        8 + // curve_ratio
-        1; // is_active
+// Quote a sell of `amount` and apply the checks every sell path shares, returning the new
+// supply, the proceeds and the fee on them
+fn quote_checked_sell(
+    launch: &mut Launch,
+    stats: &LaunchStats,
+    config: &Config,
+    amount: u64,
+    max_supply_delta_bps: u16,
+    discount_bps: u16,
+    now: i64,
+) -> Result<(u64, u64, u64)> {
+    let (new_supply, proceeds) = launch.quote_sell(amount, now)?;
+    config.check_trade_size(amount, proceeds)?;
+    launch.check_supply_delta(new_supply, max_supply_delta_bps)?;
+    let fee = fee_of(proceeds, config.trade_fee_bps(launch, false, stats, discount_bps)?)?;
+
+    // Sells are paid out of curve reserves, never the vault's rent-exempt minimum
+    require!(proceeds <= launch.sol_reserves, LaunchError::InsufficientReserves);
+    Ok((new_supply, proceeds, fee))
 }
 
-#[error_code]
-pub enum LaunchError {
-    #[msg("Launch is not active")]
-    LaunchInactive,
-    #[msg("Invalid price calculation")]
-    InvalidPriceCalculation,
+// Holder bookkeeping for a sell: loyalty balance, cost basis and competition volume
+fn record_holder_sell(
+    launch: &mut Launch,
+    config: &Config,
+    holder: &mut BuyerState,
+    amount: u64,
+    proceeds: u64,
+    now: i64,
+) {
+    let balance = holder.loyalty_balance.saturating_sub(amount);
+    launch.sync_loyalty(holder, balance, now);
+    launch.remove_cost_basis(holder, amount);
+    config.record_competition_volume(holder, proceeds, now);
+}
+
+fn process_sell(
+    ctx: Context<Trade>,
+    amount: u64,
+    min_proceeds: u64,
+    payout_mode: PayoutMode,
+) -> Result<TradeResult> {
+    require_not_blacklisted(&ctx.accounts.blacklist_entry)?;
+    require_fresh_tx(&ctx.accounts.launch, ctx.accounts.instructions.as_ref())?;
+    require_buyer_state(&ctx.accounts.buyer_state, &ctx.accounts.buyer_state_address)?;
+    require!(ctx.accounts.trade_receipt.is_none(), LaunchError::InvalidTradeReceipt);
+    let now = Clock::get()?.unix_timestamp;
+    let launch = &mut ctx.accounts.launch;
+    require!(!launch.trade_in_flight, LaunchError::ReentrantTrade);
+    launch.check_mint_supply(&ctx.accounts.mint)?;
+
+    let config = &ctx.accounts.config;
+    let market_maker = launch.market_maker(
+        ctx.accounts.market_maker_entry.as_deref(),
+        config.market_maker_window_secs,
+        now,
+    );
+    let stats = &mut ctx.accounts.stats;
+    require!(now >= stats.sells_paused_until, LaunchError::SellsPaused);
+    stats.roll_window(now, config.velocity_window_secs);
+    let discount_bps =
+        config.holder_discount_bps(&ctx.accounts.trader.key(), ctx.remaining_accounts)?;
+    let (new_supply, proceeds, fee) = quote_checked_sell(
+        launch,
+        stats,
+        config,
+        amount,
+        market_maker.map_or(config.max_supply_delta_bps, |entry| entry.max_supply_delta_bps),
+        discount_bps,
+        now,
+    )?;
+    let payout = proceeds - fee;
+    require!(payout >= min_proceeds, LaunchError::SlippageExceeded);
+
+    // Catch a frozen account here rather than as an opaque token-program failure on burn
+    read_trader_account(&ctx.accounts.trader_token_account.to_account_info(), false)?;
+
+    // As with buys, commit and persist the trade under the in-flight guard before any CPI.
+    // The treasury's cut of the fee is left in the vault and accrued for a later claim.
+    let frontend_share =
+        config.frontend_share(fee, ctx.accounts.frontend_fee_recipient.is_some())?;
+    launch.record_sell(new_supply, proceeds);
+    launch.accrued_protocol_fees += fee - frontend_share;
+    stats.record_trade(proceeds);
+    stats.track_price(launch, launch.spot_price()?, now)?;
+    if let Some(holder) = ctx.accounts.buyer_state.as_deref_mut() {
+        record_holder_sell(launch, config, holder, amount, proceeds, now);
+    }
+    launch.trade_in_flight = true;
+    launch.exit(ctx.program_id)?;
+    stats.exit(ctx.program_id)?;
+
+    // Burn the sold tokens from the trader
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.trader_token_account.to_account_info(),
+                authority: ctx.accounts.trader.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    // Pay the trader and any routing frontend out of the launch vault. wSOL payouts land in
+    // the trader's native-mint account and are wrapped by syncing it afterwards.
+    let payout_recipient = match payout_mode {
+        PayoutMode::Native => ctx.accounts.trader.to_account_info(),
+        PayoutMode::Wsol => ctx
+            .accounts
+            .trader_wsol_account
+            .as_ref()
+            .ok_or(LaunchError::WsolAccountRequired)?
+            .to_account_info(),
+    };
+    let mut payouts = vec![(payout_recipient.clone(), payout)];
+    if let Some(frontend) = ctx.accounts.frontend_fee_recipient.as_ref() {
+        payouts.push((frontend.to_account_info(), frontend_share));
+    }
+    let launch_key = launch.key();
+    let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[ctx.bumps.vault]]];
+    for (recipient, lamports) in payouts {
+        if lamports == 0 {
+            continue;
+        }
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.vault.key(),
+            recipient.key,
+            lamports,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                recipient,
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            vault_seeds,
+        )?;
+    }
+    if payout_mode == PayoutMode::Wsol {
+        token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::SyncNative { account: payout_recipient },
+        ))?;
+    }
+
+    launch.trade_in_flight = false;
+
+    emit!(TradeEvent {
+        seq: launch.next_trade_seq(),
+        launch: launch_key,
+        trader: ctx.accounts.trader.key(),
+        is_buy: false,
+        amount,
+        price: proceeds,
+        fee,
+        lamports: payout,
+        limit: min_proceeds,
+        total_supply: launch.total_supply,
+        timestamp: now,
+    });
+
+    let result = TradeResult {
+        amount,
+        lamports: payout,
+        fee,
+        spot_price: launch.spot_price()?,
+        total_supply: launch.total_supply,
+        remaining_supply: launch.max_supply - launch.total_supply,
+    };
+    invoke_trade_hook(
+        &ctx.accounts.launch,
+        ctx.accounts.hook_program.as_ref(),
+        ctx.accounts.hook_allowlist_entry.as_deref(),
+        ctx.accounts.trader.to_account_info(),
+        false,
+        result,
+    )?;
+    append_trade_record(
+        &ctx.accounts.launch,
+        ctx.accounts.trade_tree.as_ref(),
+        ctx.accounts.tree_authority.as_ref(),
+        ctx.bumps.tree_authority,
+        ctx.accounts.compression_program.as_ref(),
+        ctx.accounts.noop_program.as_ref(),
+        TradeHookArgs { trader: ctx.accounts.trader.key(), is_buy: false, result },
+    )?;
+    Ok(result)
+}
+
+// Create and initialize one launch of a batch from its remaining-account group, mirroring
+// what the InitializeLaunch constraints do for a single launch
+fn create_batch_launch<'info>(
+    accounts: &InitializeLaunchBatch<'info>,
+    program_id: &Pubkey,
+    group: &[AccountInfo<'info>],
+    params: LaunchParams,
+    bundle_leader: Pubkey,
+) -> Result<()> {
+    let [
+        launch_info,
+        mint_info,
+        mint_authority,
+        creator_token_account,
+        vault,
+        stats_info,
+        metadata_info,
+    ] = group
+    else {
+        return err!(LaunchError::InvalidBatch);
+    };
+
+    let launch_key = launch_info.key();
+    let (mint_authority_key, mint_authority_bump) = find_mint_authority_address(&launch_key);
+    let (vault_key, _) = find_vault_address(&launch_key);
+    let (stats_key, stats_bump) = find_stats_address(&launch_key);
+    require_keys_eq!(mint_authority.key(), mint_authority_key, LaunchError::InvalidBatch);
+    require_keys_eq!(vault.key(), vault_key, LaunchError::InvalidBatch);
+    require_keys_eq!(stats_info.key(), stats_key, LaunchError::InvalidBatch);
+    require_keys_eq!(
+        metadata_info.key(),
+        mpl_token_metadata::accounts::Metadata::find_pda(&mint_info.key()).0,
+        LaunchError::InvalidBatch
+    );
+    require_keys_eq!(
+        creator_token_account.key(),
+        associated_token::get_associated_token_address(&accounts.creator.key(), &mint_info.key()),
+        LaunchError::InvalidBatch
+    );
+
+    let mut launch = Launch::default();
+    launch.init(&accounts.config, accounts.creator.key(), mint_info.key(), params)?;
+    launch.bundle_leader = bundle_leader;
+
+    let rent = Rent::get()?;
+    let payer = accounts.payer.to_account_info();
+    let system_program = accounts.system_program.to_account_info();
+    let token_program = accounts.token_program.to_account_info();
+
+    // Launch and mint are fresh keypairs that sign the transaction
+    invoke(
+        &anchor_lang::solana_program::system_instruction::create_account(
+            payer.key,
+            launch_info.key,
+            rent.minimum_balance(Launch::LEN),
+            Launch::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), launch_info.clone(), system_program.clone()],
+    )?;
+    invoke(
+        &anchor_lang::solana_program::system_instruction::create_account(
+            payer.key,
+            mint_info.key,
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &token::ID,
+        ),
+        &[payer.clone(), mint_info.clone(), system_program.clone()],
+    )?;
+    token::initialize_mint2(
+        CpiContext::new(
+            token_program.clone(),
+            token::InitializeMint2 { mint: mint_info.clone() },
+        ),
+        TOKEN_DECIMALS,
+        &mint_authority_key,
+        None,
+    )?;
+    associated_token::create_idempotent(CpiContext::new(
+        accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: payer.clone(),
+            associated_token: creator_token_account.clone(),
+            authority: accounts.creator.to_account_info(),
+            mint: mint_info.clone(),
+            system_program: system_program.clone(),
+            token_program: token_program.clone(),
+        },
+    ))?;
+    invoke_signed(
+        &anchor_lang::solana_program::system_instruction::create_account(
+            payer.key,
+            stats_info.key,
+            rent.minimum_balance(LaunchStats::LEN),
+            LaunchStats::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), stats_info.clone(), system_program.clone()],
+        &[&[STATS_SEED, launch_key.as_ref(), &[stats_bump]]],
+    )?;
+    invoke(
+        &anchor_lang::solana_program::system_instruction::transfer(
+            payer.key,
+            vault.key,
+            rent.minimum_balance(0),
+        ),
+        &[payer.clone(), vault.clone(), system_program],
+    )?;
+
+    let mint_authority_seeds: &[&[&[u8]]] =
+        &[&[MINT_AUTHORITY_SEED, launch_key.as_ref(), &[mint_authority_bump]]];
+    token::mint_to(
+        CpiContext::new_with_signer(
+            token_program,
+            token::MintTo {
+                mint: mint_info.clone(),
+                to: creator_token_account.clone(),
+                authority: mint_authority.clone(),
+            },
+            mint_authority_seeds,
+        ),
+        launch.initial_supply,
+    )?;
+    create_token_metadata(
+        &launch,
+        accounts.token_metadata_program.to_account_info(),
+        metadata::CreateMetadataAccountsV3 {
+            metadata: metadata_info.clone(),
+            mint: mint_info.clone(),
+            mint_authority: mint_authority.clone(),
+            payer,
+            update_authority: mint_authority.clone(),
+            system_program: accounts.system_program.to_account_info(),
+            // The v3 instruction no longer reads the rent sysvar
+            rent: accounts.system_program.to_account_info(),
+        },
+        mint_authority_seeds,
+    )?;
+
+    let stats = LaunchStats {
+        launch: launch_key,
+        window_start: Clock::get()?.unix_timestamp,
+        bump: stats_bump,
+        ..Default::default()
+    };
+    launch.try_serialize(&mut &mut launch_info.try_borrow_mut_data()?[..])?;
+    stats.try_serialize(&mut &mut stats_info.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
+
+// CPI into the launch's hook program, if any, with the trade outcome. The hook gets the
+// launch and trader read-only and without the trader's signature.
+fn invoke_trade_hook<'info>(
+    launch: &Account<'info, Launch>,
+    hook_program: Option<&UncheckedAccount<'info>>,
+    allowlist_entry: Option<&HookAllowlistEntry>,
+    trader: AccountInfo<'info>,
+    is_buy: bool,
+    result: TradeResult,
+) -> Result<()> {
+    if launch.hook_program == Pubkey::default() {
+        return Ok(());
+    }
+    let hook_program = hook_program.ok_or(LaunchError::HookNotAllowed)?;
+    require!(allowlist_entry.is_some(), LaunchError::HookNotAllowed);
+
+    let mut data = anchor_lang::solana_program::hash::hash(b"global:on_trade").to_bytes()[..8].to_vec();
+    TradeHookArgs { trader: trader.key(), is_buy, result }.serialize(&mut data)?;
+    let hook_ix = Instruction {
+        program_id: launch.hook_program,
+        accounts: vec![
+            AccountMeta::new_readonly(launch.key(), false),
+            AccountMeta::new_readonly(trader.key(), false),
+        ],
+        data,
+    };
+    invoke(
+        &hook_ix,
+        &[launch.to_account_info(), trader, hook_program.to_account_info()],
+    )?;
+    Ok(())
+}
+
+// Append a trade record leaf to the launch's compressed trade tree, if it has one
+fn append_trade_record<'info>(
+    launch: &Account<'info, Launch>,
+    trade_tree: Option<&UncheckedAccount<'info>>,
+    tree_authority: Option<&UncheckedAccount<'info>>,
+    tree_authority_bump: Option<u8>,
+    compression_program: Option<&Program<'info, SplAccountCompression>>,
+    noop_program: Option<&Program<'info, Noop>>,
+    record: TradeHookArgs,
+) -> Result<()> {
+    if launch.trade_tree == Pubkey::default() {
+        return Ok(());
+    }
+    let (Some(trade_tree), Some(tree_authority), Some(bump), Some(compression_program), Some(noop_program)) =
+        (trade_tree, tree_authority, tree_authority_bump, compression_program, noop_program)
+    else {
+        return err!(LaunchError::InvalidTradeTree);
+    };
+
+    // The leaf commits to the launch and sequence number so records cannot be replayed
+    let launch_key = launch.key();
+    let seq = launch.trade_seq.wrapping_sub(1);
+    let leaf = keccak::hashv(&[launch_key.as_ref(), &seq.to_le_bytes(), &record.try_to_vec()?]).0;
+    spl_account_compression::cpi::append(
+        CpiContext::new_with_signer(
+            compression_program.to_account_info(),
+            spl_account_compression::cpi::accounts::Modify {
+                merkle_tree: trade_tree.to_account_info(),
+                authority: tree_authority.to_account_info(),
+                noop: noop_program.to_account_info(),
+            },
+            &[&[TREE_AUTHORITY_SEED, launch_key.as_ref(), &[bump]]],
+        ),
+        leaf,
+    )
+}
+
+// Create `owner`'s ATA if it is missing, with rent from `payer`, and return its balance.
+// The idempotent create succeeds on an existing account and needs no rent sysvar.
+fn ensure_token_account<'info>(
+    payer: &Signer<'info>,
+    token_account: &UncheckedAccount<'info>,
+    owner: AccountInfo<'info>,
+    mint: &Account<'info, Mint>,
+    system_program: &Program<'info, System>,
+    token_program: &Program<'info, Token>,
+    associated_token_program: &Program<'info, AssociatedToken>,
+) -> Result<u64> {
+    require_keys_eq!(
+        token_account.key(),
+        associated_token::get_associated_token_address(owner.key, &mint.key()),
+        LaunchError::InvalidTokenAccount
+    );
+
+    associated_token::create_idempotent(CpiContext::new(
+        associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: payer.to_account_info(),
+            associated_token: token_account.to_account_info(),
+            authority: owner,
+            mint: mint.to_account_info(),
+            system_program: system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+    ))?;
+
+    // Minted tokens must land somewhere the trader alone controls and can move
+    let account = read_trader_account(&token_account.to_account_info(), true)?;
+    Ok(account.amount)
+}
+
+// Load a trader's token account, rejecting one that is frozen or, when the tokens are about to
+// land in it, one with a delegate that could move them
+fn read_trader_account(info: &AccountInfo, reject_delegate: bool) -> Result<TokenAccount> {
+    require_keys_eq!(*info.owner, token::ID, LaunchError::InvalidTokenAccount);
+    let account = TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require!(!account.is_frozen(), LaunchError::AccountFrozen);
+    require!(
+        !reject_delegate || account.delegate.is_none(),
+        LaunchError::UnexpectedDelegate
+    );
+    Ok(account)
+}
+
+// The launch's name, symbol and URI as Metaplex metadata, with no royalties or collection
+fn token_metadata_data(launch: &Launch) -> mpl_token_metadata::types::DataV2 {
+    mpl_token_metadata::types::DataV2 {
+        name: launch.name.clone(),
+        symbol: launch.symbol.clone(),
+        uri: launch.metadata_uri.clone(),
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    }
+}
+
+// Publish the mint's Metaplex metadata. The mint authority PDA is its update authority, so
+// only this program can change it. Immutable launches publish their final URI here and can
+// never change it; the rest stay mutable so set_metadata_uri can update it later.
+fn create_token_metadata<'info>(
+    launch: &Launch,
+    token_metadata_program: AccountInfo<'info>,
+    accounts: metadata::CreateMetadataAccountsV3<'info>,
+    mint_authority_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    metadata::create_metadata_accounts_v3(
+        CpiContext::new_with_signer(token_metadata_program, accounts, mint_authority_seeds),
+        token_metadata_data(launch),
+        !launch.immutable_metadata,
+        true,
+        None,
+    )
+}
+
+// Split a vault release between the protocol treasury and the creator's fee destination
+fn pay_out_proceeds(accounts: &DistributeProceeds, vault_bump: u8, release: u64) -> Result<()> {
+    let protocol_share = bps_of(release, accounts.config.protocol_fee_bps)?;
+    let creator_share = release - protocol_share;
+
+    let launch_key = accounts.launch.key();
+    let vault_seeds: &[&[&[u8]]] = &[&[VAULT_SEED, launch_key.as_ref(), &[vault_bump]]];
+
+    for (recipient, lamports) in [
+        (accounts.treasury.to_account_info(), protocol_share),
+        (accounts.fee_destination.to_account_info(), creator_share),
+    ] {
+        if lamports == 0 {
+            continue;
+        }
+        let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+            &accounts.vault.key(),
+            recipient.key,
+            lamports,
+        );
+        invoke_signed(
+            &transfer_ix,
+            &[
+                accounts.vault.to_account_info(),
+                recipient,
+                accounts.system_program.to_account_info(),
+            ],
+            vault_seeds,
+        )?;
+    }
+    Ok(())
+}
+
+// Move an OTC offer's escrowed tokens to `to` and return the escrow rent to the seller
+fn release_otc_escrow<'info>(
+    offer: &Account<'info, OtcOffer>,
+    escrow: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    seller: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let offer_seeds: &[&[&[u8]]] = &[&[
+        OTC_OFFER_SEED,
+        offer.launch.as_ref(),
+        offer.seller.as_ref(),
+        &[offer.bump],
+    ]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: escrow.to_account_info(),
+                to: to.to_account_info(),
+                authority: offer.to_account_info(),
+            },
+            offer_seeds,
+        ),
+        offer.amount,
+    )?;
+    token::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        token::CloseAccount {
+            account: escrow.to_account_info(),
+            destination: seller.clone(),
+            authority: offer.to_account_info(),
+        },
+        offer_seeds,
+    ))
+}
+
+// Helper function to read the SetComputeUnitPrice value attached to the current transaction
+fn compute_unit_price(instructions: &AccountInfo) -> Result<u64> {
+    const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == COMPUTE_BUDGET_PROGRAM_ID
+            && ix.data.len() >= 9
+            && ix.data[0] == SET_COMPUTE_UNIT_PRICE
+        {
+            let mut price = [0u8; 8];
+            price.copy_from_slice(&ix.data[1..9]);
+            return Ok(u64::from_le_bytes(price));
+        }
+        index += 1;
+    }
+    Ok(0)
+}
+
+// Helper function to require a prove_freshness instruction in the current transaction when
+// the launch runs in fresh-tx mode
+fn require_fresh_tx(launch: &Launch, instructions: Option<&UncheckedAccount>) -> Result<()> {
+    if !launch.fresh_tx_required {
+        return Ok(());
+    }
+    let instructions = instructions.ok_or(LaunchError::StaleTransaction)?;
+
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == crate::ID
+            && ix.data.starts_with(&instruction::ProveFreshness::DISCRIMINATOR)
+        {
+            return Ok(());
+        }
+        index += 1;
+    }
+    err!(LaunchError::StaleTransaction)
 }
 
-// Helper function to calculate price based on bonding curve
-fn calculate_price(current_supply: u64, amount: u64, curve_ratio: u64) -> Result<u64> {
-    // Simple linear bonding curve: price = current_supply * curve_ratio * amount
-    let price = (current_supply as u128)
-        .checked_mul(curve_ratio as u128)
-        .ok_or(LaunchError::InvalidPriceCalculation)?
-        .checked_mul(amount as u128)
+// Helper function to reject wallets with a blacklist entry; only this program can create one
+fn require_not_blacklisted(entry: &AccountInfo) -> Result<()> {
+    require!(entry.data_is_empty(), LaunchError::Blacklisted);
+    Ok(())
+}
+
+// A trader whose BuyerState exists must pass it, so caps and basis always apply
+fn require_buyer_state(state: &Option<Account<BuyerState>>, address: &AccountInfo) -> Result<()> {
+    require!(state.is_some() || address.data_is_empty(), LaunchError::BuyerStateRequired);
+    Ok(())
+}
+
+// Helper function to verify a keccak merkle proof with sorted sibling pairs
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for sibling in proof.iter() {
+        computed = if computed <= *sibling {
+            keccak::hashv(&[&computed, sibling]).0
+        } else {
+            keccak::hashv(&[sibling, &computed]).0
+        };
+    }
+    computed == root
+}
+
+// Balance-seconds accrued holding `balance` from `from` until `until`
+fn holding_points(balance: u64, from: i64, until: i64) -> u128 {
+    balance as u128 * until.saturating_sub(from).max(0) as u128
+}
+
+// Helper function to take a basis-point share of a lamport amount, rounding down
+// Rounds down: use for shares paid out of an amount
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    bps_of_rounded(amount, bps, Rounding::Down)
+}
+
+// Rounds up: use for fees charged to a trader, so no trade can be sized to skip its fee
+fn fee_of(amount: u64, bps: u16) -> Result<u64> {
+    bps_of_rounded(amount, bps, Rounding::Up)
+}
+
+fn bps_of_rounded(amount: u64, bps: u16, rounding: Rounding) -> Result<u64> {
+    let product = (amount as u128)
+        .checked_mul(bps as u128)
         .ok_or(LaunchError::InvalidPriceCalculation)?;
-    
-    Ok(price.try_into().map_err(|_| LaunchError::InvalidPriceCalculation)?)
-}
\ No newline at end of file
+    let share = rounding.div(product, MAX_BPS as u128);
+
+    Ok(share.try_into().map_err(|_| LaunchError::InvalidPriceCalculation)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::Bumps;
+    use token::spl_token::state::{Account as SplTokenAccount, AccountState};
+
+    const UNIT: u64 = 1_000_000_000;
+    // Round trips are checked with and without a trading fee, so fees cannot mask curve drift
+    const FEE_BPS: [u16; 2] = [0, 100];
+    // Deliberately uneven trade sizes, down to single base units
+    const PIECES: [u64; 6] = [1, 3 * UNIT + 7, UNIT / 3, 250 * UNIT, 1, 999_999];
+
+    // Account infos live for the whole test binary, as `try_accounts` needs `'info` data. Every
+    // account but a program is writable, so `mut` constraints pass.
+    fn account_info(
+        key: Pubkey,
+        owner: Pubkey,
+        data: Vec<u8>,
+        is_signer: bool,
+        executable: bool,
+    ) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            !executable,
+            Box::leak(Box::new(0)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            executable,
+            0,
+        )
+    }
+
+    fn token_account_info(
+        key: Pubkey,
+        program: Pubkey,
+        account: SplTokenAccount,
+    ) -> AccountInfo<'static> {
+        let mut data = vec![0; SplTokenAccount::LEN];
+        SplTokenAccount::pack(account, &mut data).unwrap();
+        account_info(key, program, data, false, false)
+    }
+
+    fn linear_launch() -> Launch {
+        Launch {
+            curve_ratio: CURVE_RATIO_SCALE,
+            decimals: TOKEN_DECIMALS,
+            max_supply: 1_000_000_000 * UNIT,
+            ..Default::default()
+        }
+    }
+
+    fn virtual_launch() -> Launch {
+        Launch {
+            curve_type: CurveType::VirtualReserves,
+            virtual_sol_reserves: 30 * UNIT,
+            virtual_token_reserves: 1_073_000_000 * UNIT,
+            ..linear_launch()
+        }
+    }
+
+    // Linear curve that steepens 1.5x two tokens past `start` and 2x two hundred tokens past it
+    fn staged_launch(start: u64) -> Launch {
+        let mut launch = linear_launch();
+        launch.curve_stage_count = 2;
+        launch.curve_stages[0] =
+            CurveStage { supply_threshold: start + 2 * UNIT, multiplier_bps: 15_000 };
+        launch.curve_stages[1] =
+            CurveStage { supply_threshold: start + 200 * UNIT, multiplier_bps: 20_000 };
+        launch
+    }
+
+    // Buy every piece from `start` in one trade, then sell it back piece by piece. Returns the
+    // lamports paid and the lamports paid back, fees included.
+    fn buy_once_sell_in_pieces(
+        cost: impl Fn(u64, u64, Rounding) -> u64,
+        start: u64,
+        pieces: &[u64],
+        fee_bps: u16,
+    ) -> (u64, u64) {
+        let amount: u64 = pieces.iter().sum();
+        let price = cost(start, amount, Rounding::Up);
+        let paid = price + fee_of(price, fee_bps).unwrap();
+
+        let mut supply = start + amount;
+        let mut returned = 0;
+        for piece in pieces {
+            supply -= piece;
+            let proceeds = cost(supply, *piece, Rounding::Down);
+            returned += proceeds - fee_of(proceeds, fee_bps).unwrap();
+        }
+        (paid, returned)
+    }
+
+    // Buy piece by piece from `start`, then sell everything back in one trade
+    fn buy_in_pieces_sell_once(
+        cost: impl Fn(u64, u64, Rounding) -> u64,
+        start: u64,
+        pieces: &[u64],
+        fee_bps: u16,
+    ) -> (u64, u64) {
+        let mut supply = start;
+        let mut paid = 0;
+        for piece in pieces {
+            let price = cost(supply, *piece, Rounding::Up);
+            paid += price + fee_of(price, fee_bps).unwrap();
+            supply += piece;
+        }
+
+        let proceeds = cost(start, supply - start, Rounding::Down);
+        (paid, proceeds - fee_of(proceeds, fee_bps).unwrap())
+    }
+
+    #[test]
+    fn linear_round_trips_never_pay_out_more_than_paid_in() {
+        let launch = linear_launch();
+        let cost = |start, amount, rounding| launch.staged_cost(start, amount, rounding).unwrap();
+        for (start, fee_bps) in [0, 1_000_000 * UNIT, 999_000_000 * UNIT]
+            .into_iter()
+            .flat_map(|start| FEE_BPS.map(|fee_bps| (start, fee_bps)))
+        {
+            let (paid, returned) = buy_once_sell_in_pieces(cost, start, &PIECES, fee_bps);
+            assert!(returned <= paid, "start {start}: paid {paid}, returned {returned}");
+            let (paid, returned) = buy_in_pieces_sell_once(cost, start, &PIECES, fee_bps);
+            assert!(returned <= paid, "start {start}: paid {paid}, returned {returned}");
+        }
+    }
+
+    #[test]
+    fn linear_cost_does_not_depend_on_the_split() {
+        let launch = linear_launch();
+        let start = 1_000_000 * UNIT;
+        let amount: u64 = PIECES.iter().sum();
+        let whole = launch.staged_cost(start, amount, Rounding::Down).unwrap();
+
+        let mut supply = start;
+        let mut split = 0;
+        for piece in PIECES {
+            split += launch.staged_cost(supply, piece, Rounding::Down).unwrap();
+            supply += piece;
+        }
+        // Each piece rounds down on its own, so the split can only lose dust
+        assert!(split <= whole && whole - split <= PIECES.len() as u64 * 2);
+    }
+
+    #[test]
+    fn staged_round_trips_never_pay_out_more_than_paid_in() {
+        let launch = staged_launch(1_000_000 * UNIT);
+        let cost = |start, amount, rounding| launch.staged_cost(start, amount, rounding).unwrap();
+        for fee_bps in FEE_BPS {
+            let (paid, returned) =
+                buy_once_sell_in_pieces(cost, 1_000_000 * UNIT, &PIECES, fee_bps);
+            assert!(returned <= paid, "paid {paid}, returned {returned}");
+            let (paid, returned) =
+                buy_in_pieces_sell_once(cost, 1_000_000 * UNIT, &PIECES, fee_bps);
+            assert!(returned <= paid, "paid {paid}, returned {returned}");
+        }
+    }
+
+    #[test]
+    fn virtual_reserves_round_trips_never_pay_out_more_than_paid_in() {
+        let launch = virtual_launch();
+        let cost = |start, amount, rounding| {
+            launch.virtual_reserves_cost(start, amount, rounding).unwrap()
+        };
+        for (start, fee_bps) in [0, 500_000_000 * UNIT]
+            .into_iter()
+            .flat_map(|start| FEE_BPS.map(|fee_bps| (start, fee_bps)))
+        {
+            let (paid, returned) = buy_once_sell_in_pieces(cost, start, &PIECES, fee_bps);
+            assert!(returned <= paid, "start {start}: paid {paid}, returned {returned}");
+            let (paid, returned) = buy_in_pieces_sell_once(cost, start, &PIECES, fee_bps);
+            assert!(returned <= paid, "start {start}: paid {paid}, returned {returned}");
+        }
+    }
+
+    #[test]
+    fn holder_buys_accrue_loyalty_on_the_tracked_balance() {
+        let mut launch = linear_launch();
+        let mut holder = BuyerState::default();
+        let config = Config::default();
+        record_holder_buy(&mut launch, &config, &mut holder, 5 * UNIT, UNIT, 0, 100);
+        assert_eq!(holder.loyalty_balance, 5 * UNIT);
+        assert_eq!(launch.loyalty_supply, 5 * UNIT);
+        assert_eq!(holder.loyalty_points, 0);
+
+        record_holder_buy(&mut launch, &config, &mut holder, 8 * UNIT, UNIT, 5 * UNIT, 160);
+        assert_eq!(holder.loyalty_balance, 8 * UNIT);
+        assert_eq!(launch.loyalty_supply, 8 * UNIT);
+        assert_eq!(holder.loyalty_points, holding_points(5 * UNIT, 100, 160));
+        assert_eq!(launch.loyalty_points, holding_points(5 * UNIT, 100, 160));
+    }
+
+    #[test]
+    fn holder_buys_take_supporter_numbers_until_the_limit() {
+        let mut launch = Launch { early_supporter_limit: 2, ..linear_launch() };
+        let config = Config::default();
+        let mut holders = [BuyerState::default(), BuyerState::default(), BuyerState::default()];
+        for holder in holders.iter_mut() {
+            record_holder_buy(&mut launch, &config, holder, UNIT, UNIT, 0, 0);
+        }
+        // A repeat buy keeps its number and does not take another slot
+        record_holder_buy(&mut launch, &config, &mut holders[0], UNIT, UNIT, 0, 0);
+        assert_eq!(holders.map(|holder| holder.supporter_number), [1, 2, 0]);
+        assert_eq!(launch.early_supporter_count, 2);
+    }
+
+    #[test]
+    fn holder_buys_count_toward_the_running_competition() {
+        let mut launch = linear_launch();
+        let config =
+            Config { competition_epoch: 3, competition_ends_at: 1_000, ..Default::default() };
+        // Volume from an earlier competition is reset, not carried over
+        let mut holder =
+            BuyerState { competition_epoch: 2, competition_volume: 9, ..Default::default() };
+        record_holder_buy(&mut launch, &config, &mut holder, UNIT, 4 * UNIT, 0, 10);
+        record_holder_buy(&mut launch, &config, &mut holder, UNIT, UNIT, 0, 20);
+        assert_eq!((holder.competition_epoch, holder.competition_volume), (3, 5 * UNIT));
+
+        // Volume after the competition closes is not counted
+        record_holder_buy(&mut launch, &config, &mut holder, UNIT, UNIT, 0, 1_000);
+        assert_eq!(holder.competition_volume, 5 * UNIT);
+    }
+
+    #[test]
+    fn holder_buys_below_the_cutoff_earn_lp_units() {
+        let mut launch = Launch { lp_unit_cutoff_supply: 10 * UNIT, ..linear_launch() };
+        let config = Config::default();
+        let mut holder = BuyerState::default();
+        record_holder_buy(&mut launch, &config, &mut holder, UNIT, 3 * UNIT, 0, 0);
+        // A buy starting just under the cutoff still counts in full
+        record_holder_buy(&mut launch, &config, &mut holder, UNIT, 2 * UNIT, 10 * UNIT - 1, 0);
+        record_holder_buy(&mut launch, &config, &mut holder, UNIT, UNIT, 10 * UNIT, 0);
+        assert_eq!(holder.lp_units, 5 * UNIT);
+        assert_eq!(launch.total_lp_units, 5 * UNIT);
+    }
+
+    // Run the account validation of context `C` over `accounts` followed by `programs`, with
+    // the program at `spoofed` swapped for a look-alike key
+    fn validate_context<C>(
+        mut accounts: Vec<AccountInfo<'static>>,
+        programs: &[Pubkey],
+        spoofed: Option<usize>,
+    ) -> Result<C>
+    where
+        C: Accounts<'static, C::Bumps> + Bumps,
+        C::Bumps: Default,
+    {
+        for (index, program) in programs.iter().enumerate() {
+            let key = if spoofed == Some(index) { Pubkey::new_unique() } else { *program };
+            accounts.push(account_info(key, Pubkey::default(), vec![], false, true));
+        }
+        C::try_accounts(
+            &crate::ID,
+            &mut &*Box::leak(accounts.into_boxed_slice()),
+            &[],
+            &mut C::Bumps::default(),
+            &mut BTreeSet::new(),
+        )
+    }
+
+    // Every program account of `C` must be the real program. With all of them real, validation
+    // gets past the programs, to success or to a later constraint.
+    fn assert_rejects_spoofed_programs<C>(
+        accounts: impl Fn() -> Vec<AccountInfo<'static>>,
+        programs: &[Pubkey],
+    ) where
+        C: Accounts<'static, C::Bumps> + Bumps,
+        C::Bumps: Default,
+    {
+        if let Err(err) = validate_context::<C>(accounts(), programs, None) {
+            assert_ne!(err, ErrorCode::InvalidProgramId.into());
+        }
+        for spoofed in 0..programs.len() {
+            let Err(err) = validate_context::<C>(accounts(), programs, Some(spoofed)) else {
+                panic!("program {spoofed} accepted");
+            };
+            assert_eq!(err, ErrorCode::InvalidProgramId.into(), "program {spoofed}");
+        }
+    }
+
+    fn anchor_account<T: AccountSerialize>(key: Pubkey, account: T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        account_info(key, crate::ID, data, false, false)
+    }
+
+    // An optional account left out, passed as the program ID
+    fn absent() -> AccountInfo<'static> {
+        account_info(crate::ID, Pubkey::default(), vec![], false, false)
+    }
+
+    fn config_info() -> AccountInfo<'static> {
+        let (key, bump) = Pubkey::find_program_address(&[CONFIG_SEED], &crate::ID);
+        anchor_account(key, Config { bump, ..Default::default() })
+    }
+
+    fn mint_info(key: Pubkey) -> AccountInfo<'static> {
+        let mint = token::spl_token::state::Mint { is_initialized: true, ..Default::default() };
+        let mut data = vec![0; token::spl_token::state::Mint::LEN];
+        token::spl_token::state::Mint::pack(mint, &mut data).unwrap();
+        account_info(key, token::ID, data, false, false)
+    }
+
+    fn system_info(key: Pubkey, is_signer: bool) -> AccountInfo<'static> {
+        account_info(key, System::id(), vec![], is_signer, false)
+    }
+
+    // The launch, vault and stats accounts every trade context opens with
+    fn trade_launch_accounts(launch: Pubkey, mint: Pubkey) -> [AccountInfo<'static>; 3] {
+        let (stats, bump) = find_stats_address(&launch);
+        [
+            anchor_account(launch, Launch { mint, ..linear_launch() }),
+            system_info(find_vault_address(&launch).0, false),
+            anchor_account(stats, LaunchStats { launch, bump, ..Default::default() }),
+        ]
+    }
+
+    const TRADE_PROGRAMS: [Pubkey; 3] =
+        [anchor_lang::system_program::ID, token::ID, associated_token::ID];
+
+    fn trade_accounts() -> Vec<AccountInfo<'static>> {
+        let [launch, mint, trader] = [(); 3].map(|_| Pubkey::new_unique());
+        let [launch_info, vault, stats] = trade_launch_accounts(launch, mint);
+        let mut accounts = vec![
+            launch_info,
+            system_info(trader, true),
+            system_info(find_blacklist_address(&trader).0, false),
+            absent(),
+            vault,
+            stats,
+            config_info(),
+            absent(),
+            mint_info(mint),
+            system_info(find_mint_authority_address(&launch).0, false),
+            system_info(Pubkey::new_unique(), false),
+            absent(),
+            system_info(trader, true),
+        ];
+        // Attestation through buyer state are all optional
+        accounts.extend((0..13).map(|_| absent()));
+        accounts.push(system_info(find_buyer_state_address(&launch, &trader).0, false));
+        accounts
+    }
+
+    fn sell_for_many_accounts() -> Vec<AccountInfo<'static>> {
+        let (launch, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let [launch_info, vault, stats] = trade_launch_accounts(launch, mint);
+        vec![
+            launch_info,
+            system_info(Pubkey::new_unique(), true),
+            vault,
+            stats,
+            config_info(),
+            mint_info(mint),
+            absent(),
+            absent(),
+            absent(),
+        ]
+    }
+
+    const LAUNCH_PROGRAMS: [Pubkey; 4] =
+        [anchor_lang::system_program::ID, token::ID, associated_token::ID, mpl_token_metadata::ID];
+
+    // A new launch whose creator already has an ATA for the mint. The accounts `init` creates
+    // are system-owned and sized for the data RuntimeStubs leaves in them.
+    fn initialize_launch_accounts() -> Vec<AccountInfo<'static>> {
+        let [launch, mint, creator] = [(); 3].map(|_| Pubkey::new_unique());
+        let fresh = |key: Pubkey, data_len: usize, is_signer: bool| {
+            account_info(key, System::id(), vec![0; data_len], is_signer, false)
+        };
+        let creator_ata = SplTokenAccount {
+            mint,
+            owner: creator,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        let creator_ata = token_account_info(
+            associated_token::get_associated_token_address(&creator, &mint),
+            token::ID,
+            creator_ata,
+        );
+        **creator_ata.try_borrow_mut_lamports().unwrap() =
+            Rent::default().minimum_balance(SplTokenAccount::LEN);
+        vec![
+            fresh(launch, Launch::LEN, true),
+            config_info(),
+            system_info(creator, false),
+            system_info(Pubkey::new_unique(), true),
+            fresh(mint, Mint::LEN, true),
+            system_info(find_mint_authority_address(&launch).0, false),
+            creator_ata,
+            system_info(find_vault_address(&launch).0, false),
+            fresh(find_stats_address(&launch).0, LaunchStats::LEN, false),
+            system_info(mpl_token_metadata::accounts::Metadata::find_pda(&mint).0, false),
+        ]
+    }
+
+    fn launch_batch_accounts(creator: Pubkey) -> Vec<AccountInfo<'static>> {
+        vec![config_info(), system_info(creator, false), system_info(Pubkey::new_unique(), true)]
+    }
+
+    #[test]
+    fn spoofed_program_accounts_are_rejected() {
+        anchor_lang::solana_program::program_stubs::set_syscall_stubs(Box::new(RuntimeStubs));
+        assert!(validate_context::<Trade>(trade_accounts(), &TRADE_PROGRAMS, None).is_ok());
+        assert_rejects_spoofed_programs::<Trade>(trade_accounts, &TRADE_PROGRAMS);
+        let programs = &TRADE_PROGRAMS[..2];
+        assert!(validate_context::<SellForMany>(sell_for_many_accounts(), programs, None).is_ok());
+        assert_rejects_spoofed_programs::<SellForMany>(sell_for_many_accounts, programs);
+        let init = initialize_launch_accounts;
+        assert!(validate_context::<InitializeLaunch>(init(), &LAUNCH_PROGRAMS, None).is_ok());
+        assert_rejects_spoofed_programs::<InitializeLaunch>(init, &LAUNCH_PROGRAMS);
+        let batch = || launch_batch_accounts(Pubkey::new_unique());
+        assert!(validate_context::<InitializeLaunchBatch>(batch(), &LAUNCH_PROGRAMS, None).is_ok());
+        assert_rejects_spoofed_programs::<InitializeLaunchBatch>(batch, &LAUNCH_PROGRAMS);
+    }
+
+    #[test]
+    fn curve_ratio_parts_round_trip() {
+        let max_whole = u64::MAX / CURVE_RATIO_SCALE;
+        for (whole, nanos) in [(0, 0), (0, 1), (1, 0), (3, 250_000_000), (0, CURVE_RATIO_SCALE - 1)]
+            .into_iter()
+            .chain([(max_whole, u64::MAX % CURVE_RATIO_SCALE)])
+        {
+            let ratio = curve_ratio_from_parts(whole, nanos).unwrap();
+            assert_eq!(curve_ratio_to_parts(ratio), (whole, nanos));
+        }
+        assert_eq!(curve_ratio_from_parts(0, CURVE_RATIO_SCALE), None);
+        assert_eq!(curve_ratio_from_parts(max_whole + 1, 0), None);
+        assert_eq!(curve_ratio_from_parts(max_whole, u64::MAX % CURVE_RATIO_SCALE + 1), None);
+    }
+
+    #[test]
+    fn fractional_curve_ratios_price_exactly() {
+        let price = |start, amount, ratio, rounding| {
+            calculate_price(start, amount, ratio, TOKEN_DECIMALS, rounding).unwrap()
+        };
+        // 0.5 lamports per token of slope over the first two tokens is one lamport
+        assert_eq!(price(0, 2 * UNIT, CURVE_RATIO_SCALE / 2, Rounding::Down), 1);
+        assert_eq!(price(0, 2 * UNIT, 2 * CURVE_RATIO_SCALE, Rounding::Down), 4);
+        // The smallest slope, one nano-lamport, still prices a million tokens exactly
+        for rounding in [Rounding::Down, Rounding::Up] {
+            assert_eq!(price(0, 1_000_000 * UNIT, 1, rounding), 500);
+        }
+    }
+
+    #[test]
+    fn curve_prices_bracket_the_exact_area() {
+        let unit = UNIT as u128;
+        for (start, amount, ratio) in [
+            (0, 1, 1),
+            (7, UNIT / 3, 333_333_333),
+            (12_345 * UNIT + 1, 999_999, CURVE_RATIO_SCALE + 1),
+            (1_000_000 * UNIT, 3 * UNIT + 7, 2_500_000_001),
+        ] {
+            let down = calculate_price(start, amount, ratio, TOKEN_DECIMALS, Rounding::Down);
+            let up = calculate_price(start, amount, ratio, TOKEN_DECIMALS, Rounding::Up);
+            // The exact area in lamports, scaled by the full divisor
+            let area = amount as u128 * (2 * start as u128 + amount as u128) * ratio as u128;
+            let divisor = 2 * unit * unit * CURVE_RATIO_SCALE as u128;
+            assert!(down.unwrap() as u128 <= area / divisor, "{start} + {amount} at {ratio}");
+            assert!(up.unwrap() as u128 >= area.div_ceil(divisor), "{start} + {amount} at {ratio}");
+        }
+    }
+
+    #[test]
+    fn curve_prices_past_u64_fail_instead_of_wrapping() {
+        for rounding in [Rounding::Down, Rounding::Up] {
+            let half = u64::MAX / 2;
+            assert!(calculate_price(half, half, u64::MAX, TOKEN_DECIMALS, rounding).is_none());
+            assert!(calculate_price(u64::MAX, u64::MAX, 1, 0, rounding).is_none());
+        }
+    }
+
+    #[test]
+    fn staged_cost_prices_each_stage_in_whole_tokens_for_every_decimal_setting() {
+        for decimals in [0, 6, TOKEN_DECIMALS] {
+            let unit = 10u64.pow(decimals as u32);
+            // A slope of 2 lamports per token, steepened to 3 at 10 tokens and 6 at 20
+            let mut launch = Launch {
+                decimals,
+                curve_ratio: 2 * CURVE_RATIO_SCALE,
+                curve_stage_count: 2,
+                ..linear_launch()
+            };
+            launch.curve_stages[0] =
+                CurveStage { supply_threshold: 10 * unit, multiplier_bps: 15_000 };
+            launch.curve_stages[1] =
+                CurveStage { supply_threshold: 20 * unit, multiplier_bps: 20_000 };
+            for (start, amount, area) in [
+                (0, 10, 100),
+                (10, 10, 450),
+                (20, 10, 1_500),
+                (5, 4, 56),
+                // Crossing both thresholds prices each stage at its own ratio
+                (5, 20, 75 + 450 + 675),
+            ] {
+                for rounding in [Rounding::Down, Rounding::Up] {
+                    let cost = launch.staged_cost(start * unit, amount * unit, rounding).unwrap();
+                    assert_eq!(cost, area, "{decimals} decimals: {start} + {amount} tokens");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn trader_accounts_must_be_unfrozen_and_undelegated() {
+        let open = SplTokenAccount {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 42,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        let frozen = SplTokenAccount { state: AccountState::Frozen, ..open };
+        let delegated = SplTokenAccount { delegate: COption::Some(Pubkey::new_unique()), ..open };
+
+        for reject_delegate in [false, true] {
+            let info = token_account_info(Pubkey::new_unique(), token::ID, open);
+            assert_eq!(read_trader_account(&info, reject_delegate).unwrap().amount, 42);
+            let info = token_account_info(Pubkey::new_unique(), token::ID, frozen);
+            let err = read_trader_account(&info, reject_delegate).unwrap_err();
+            assert_eq!(err, LaunchError::AccountFrozen.into());
+        }
+        // Delegates only matter where tokens are minted in, not where they are sold out
+        let info = token_account_info(Pubkey::new_unique(), token::ID, delegated);
+        assert!(read_trader_account(&info, false).is_ok());
+        let err = read_trader_account(&info, true).unwrap_err();
+        assert_eq!(err, LaunchError::UnexpectedDelegate.into());
+
+        let info = token_account_info(Pubkey::new_unique(), Pubkey::new_unique(), open);
+        let err = read_trader_account(&info, false).unwrap_err();
+        assert_eq!(err, LaunchError::InvalidTokenAccount.into());
+    }
+
+    thread_local! {
+        // Every CPI the current test thread made, in order
+        static INVOKED: std::cell::RefCell<Vec<Instruction>> = Default::default();
+    }
+
+    // Serves the clock and rent sysvars and records CPIs. Of the CPIs, only account creation
+    // and mint initialization take effect, as account validation reads those back; the rest
+    // are no-ops.
+    struct RuntimeStubs;
+
+    impl anchor_lang::solana_program::program_stubs::SyscallStubs for RuntimeStubs {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Clock) = Clock::default() };
+            0
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            0
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+            use anchor_lang::solana_program::program_utils::limited_deserialize;
+            use anchor_lang::solana_program::system_instruction::SystemInstruction;
+            use token::spl_token::instruction::TokenInstruction;
+
+            INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+            let account = |index: usize| {
+                let key = instruction.accounts[index].pubkey;
+                account_infos.iter().find(|info| *info.key == key).unwrap()
+            };
+            if instruction.program_id == System::id() {
+                if let Ok(SystemInstruction::CreateAccount { lamports, owner, .. }) =
+                    limited_deserialize(&instruction.data, 1_232)
+                {
+                    let created = account(1);
+                    **created.try_borrow_mut_lamports()? += lamports;
+                    created.assign(&owner);
+                }
+            } else if instruction.program_id == token::ID {
+                if let Ok(TokenInstruction::InitializeMint2 {
+                    decimals,
+                    mint_authority,
+                    freeze_authority,
+                }) = TokenInstruction::unpack(&instruction.data)
+                {
+                    let mint = token::spl_token::state::Mint {
+                        mint_authority: COption::Some(mint_authority),
+                        decimals,
+                        is_initialized: true,
+                        freeze_authority,
+                        ..Default::default()
+                    };
+                    token::spl_token::state::Mint::pack(mint, &mut account(0).data.borrow_mut())?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn take_invoked() -> Vec<Instruction> {
+        INVOKED.with(|invoked| invoked.take())
+    }
+
+    // One launch's accounts for create_batch_launch, with the creator ATA already created for
+    // `ata_mint`, as if by an earlier interaction with it
+    fn batch_launch_group(
+        creator: Pubkey,
+        mint: Pubkey,
+        ata_mint: Pubkey,
+    ) -> Vec<AccountInfo<'static>> {
+        let launch = Pubkey::new_unique();
+        let pda = |key: Pubkey, data_len: usize| {
+            account_info(key, System::id(), vec![0; data_len], false, false)
+        };
+        let creator_ata = SplTokenAccount {
+            mint: ata_mint,
+            owner: creator,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        let creator_ata_key = associated_token::get_associated_token_address(&creator, &ata_mint);
+        vec![
+            account_info(launch, crate::ID, vec![0; Launch::LEN], true, false),
+            account_info(mint, System::id(), vec![0; Mint::LEN], true, false),
+            pda(find_mint_authority_address(&launch).0, 0),
+            token_account_info(creator_ata_key, token::ID, creator_ata),
+            pda(find_vault_address(&launch).0, 0),
+            pda(find_stats_address(&launch).0, LaunchStats::LEN),
+            pda(mpl_token_metadata::accounts::Metadata::find_pda(&mint).0, 0),
+        ]
+    }
+
+    #[test]
+    fn creators_launch_again_over_a_pre_existing_ata() {
+        anchor_lang::solana_program::program_stubs::set_syscall_stubs(Box::new(RuntimeStubs));
+        let creator = Pubkey::new_unique();
+        let accounts =
+            validate_context(launch_batch_accounts(creator), &LAUNCH_PROGRAMS, None).unwrap();
+        let params = LaunchParams {
+            name: "Relaunch".to_string(),
+            symbol: "AGAIN".to_string(),
+            initial_supply: 1_000 * UNIT,
+            curve_ratio: CURVE_RATIO_SCALE,
+            max_supply: 1_000_000 * UNIT,
+            immutable_metadata: false,
+            metadata_uri: String::new(),
+            metadata_hash: [0; 32],
+            graduation_market_cap: 0,
+            residual_policy: ResidualPolicy::Burn,
+            residual_vesting_secs: 0,
+        };
+
+        // The first launch, then a second by the same creator, each over an existing ATA
+        let first_mint = Pubkey::new_unique();
+        let second_mint = Pubkey::new_unique();
+        for mint in [first_mint, second_mint] {
+            let group = batch_launch_group(creator, mint, mint);
+            take_invoked();
+            create_batch_launch(&accounts, &crate::ID, &group, params.clone(), Pubkey::default())
+                .unwrap();
+            let data = group[0].try_borrow_data().unwrap();
+            let launch = Launch::try_deserialize(&mut &data[..]).unwrap();
+            assert_eq!((launch.creator, launch.mint), (creator, mint));
+
+            // The ATA is created with CreateIdempotent, which the ATA program accepts for an
+            // account that already exists
+            let ata_creates: Vec<_> = take_invoked()
+                .into_iter()
+                .filter(|ix| ix.program_id == associated_token::ID)
+                .collect();
+            assert_eq!(ata_creates.len(), 1);
+            assert_eq!(ata_creates[0].accounts[1].pubkey, *group[3].key);
+            assert_eq!(ata_creates[0].data, [1]);
+        }
+
+        // The ATA of the creator's earlier launch is no stand-in for the new mint's
+        let group = batch_launch_group(creator, second_mint, first_mint);
+        let err = create_batch_launch(&accounts, &crate::ID, &group, params, Pubkey::default())
+            .unwrap_err();
+        assert_eq!(err, LaunchError::InvalidBatch.into());
+    }
+
+    // Raffle commit accounts, with the randomness account owned by `randomness_owner`
+    fn commit_raffle_accounts(randomness_owner: Pubkey) -> Result<()> {
+        let launch = Pubkey::new_unique();
+        let (raffle_key, bump) =
+            Pubkey::find_program_address(&[RAFFLE_SEED, launch.as_ref()], &crate::ID);
+        let accounts = vec![
+            anchor_account(raffle_key, Raffle { launch, bump, ..Default::default() }),
+            account_info(Pubkey::new_unique(), randomness_owner, vec![0; 64], false, false),
+        ];
+        validate_context::<CommitRaffleDraw>(accounts, &[], None)?;
+        Ok(())
+    }
+
+    #[test]
+    fn raffle_randomness_must_come_from_switchboard() {
+        assert!(commit_raffle_accounts(SWITCHBOARD_ON_DEMAND_PROGRAM_ID).is_ok());
+        for owner in [Pubkey::new_unique(), crate::ID, System::id()] {
+            let err = commit_raffle_accounts(owner).unwrap_err();
+            assert_eq!(err, LaunchError::InvalidRandomness.into(), "owner {owner}");
+        }
+    }
+
+    #[test]
+    fn reservation_fills_sell_back_at_what_they_paid() {
+        let mut launch = Launch {
+            initial_supply: 1_000_000 * UNIT,
+            total_supply: 1_000_000 * UNIT,
+            ..linear_launch()
+        };
+        let (first, first_cost) = launch.fill_reservation(10 * UNIT).unwrap();
+        // Curve supply allocated between fills keeps its price as the reserved range grows
+        let (supply, pledge_cost) = launch.quote_allocation(500 * UNIT).unwrap();
+        launch.record_buy(supply, pledge_cost).unwrap();
+        let (second, second_cost) = launch.fill_reservation(3 * UNIT + 7).unwrap();
+        assert_eq!(launch.reserved_supply, first + second);
+        let paid = first_cost + pledge_cost + second_cost;
+        assert_eq!(launch.sol_reserves, paid);
+
+        // The curve supply sells back first at its curve price, then both fills at base price
+        let sells = [(500 * UNIT, pledge_cost), (second, second_cost), (first, first_cost)];
+        let mut returned = 0;
+        for (amount, cost) in sells {
+            let (new_supply, proceeds) = launch.quote_sell(amount, 0).unwrap();
+            assert!(proceeds <= cost && cost - proceeds <= 2, "paid {cost}, returned {proceeds}");
+            launch.record_sell(new_supply, proceeds);
+            returned += proceeds;
+        }
+        assert_eq!(launch.total_supply, launch.initial_supply);
+        assert_eq!(launch.sol_reserves, paid - returned);
+    }
+
+    #[test]
+    fn graduation_venues_need_a_configured_program() {
+        let dlmm_program = Pubkey::new_unique();
+        let config = Config { graduation_dex_program: Pubkey::new_unique(), ..Default::default() };
+        let mut launch = linear_launch();
+        let err = launch.set_graduation_venue(&config, GraduationVenue::Dlmm).unwrap_err();
+        assert_eq!(err, LaunchError::GraduationVenueUnavailable.into());
+
+        let config = Config { dlmm_program, ..config };
+        launch.set_graduation_venue(&config, GraduationVenue::Dlmm).unwrap();
+        assert_eq!(config.graduation_dex(launch.graduation_venue), dlmm_program);
+
+        // The venue is locked in once graduation starts
+        launch.state = LaunchState::Graduating;
+        let err = launch.set_graduation_venue(&config, GraduationVenue::Pool).unwrap_err();
+        assert_eq!(err, LaunchError::AlreadyGraduated.into());
+    }
+
+    #[test]
+    fn immutable_launches_set_their_uri_at_creation() {
+        let config = Config::default();
+        let mut params = LaunchParams {
+            name: "Fixed".to_string(),
+            symbol: "FIXED".to_string(),
+            initial_supply: 1_000 * UNIT,
+            curve_ratio: CURVE_RATIO_SCALE,
+            max_supply: 1_000_000 * UNIT,
+            immutable_metadata: true,
+            metadata_uri: String::new(),
+            metadata_hash: [0; 32],
+            graduation_market_cap: 0,
+            residual_policy: ResidualPolicy::Burn,
+            residual_vesting_secs: 0,
+        };
+        // set_metadata_uri refuses immutable launches, so the URI can't be left for later
+        assert_eq!(params.validate(&config).unwrap_err(), LaunchError::MetadataUriRequired.into());
+
+        params.metadata_uri = "https://example.com/fixed.json".to_string();
+        params.metadata_hash = [7; 32];
+        params.validate(&config).unwrap();
+        params.metadata_uri = "x".repeat(MAX_URI_LEN + 1);
+        assert_eq!(params.validate(&config).unwrap_err(), LaunchError::UriTooLong.into());
+    }
+
+    #[test]
+    fn sell_for_many_groups_carry_the_owners_discount_ata() {
+        let protocol_token_mint = Pubkey::new_unique();
+        let config = Config {
+            protocol_token_mint,
+            protocol_discount_bps: 25,
+            protocol_discount_min_balance: 100,
+            ..Default::default()
+        };
+        let owner = Pubkey::new_unique();
+        let ata = associated_token::get_associated_token_address(&owner, &protocol_token_mint);
+        let holding = SplTokenAccount {
+            mint: protocol_token_mint,
+            owner,
+            amount: 100,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        let discount_slot = token_account_info(ata, token::ID, holding);
+        let discount = |slot: &AccountInfo<'static>, owner: &Pubkey| {
+            config.holder_discount_bps(owner, std::slice::from_ref(slot)).unwrap()
+        };
+        assert_eq!(discount(&discount_slot, &owner), 25);
+
+        // Owners without a holder discount fill the slot with the default pubkey
+        let empty_slot = account_info(Pubkey::default(), System::id(), vec![], false, false);
+        assert_eq!(discount(&empty_slot, &owner), 0);
+        // Another owner's ATA doesn't discount this owner's sell
+        assert_eq!(discount(&discount_slot, &Pubkey::new_unique()), 0);
+    }
+
+    #[test]
+    fn partial_refunds_keep_the_rest_of_the_basis_claimable() {
+        // Profitable sells left reserves at half the tracked basis, so refunds pay out half
+        let mut launch = Launch {
+            state: LaunchState::Cancelled,
+            total_cost_basis: 1_000,
+            sol_reserves: 500,
+            ..linear_launch()
+        };
+        let mut holder = BuyerState { cost_basis: 600, basis_tokens: 10, ..Default::default() };
+        let claim = |launch: &mut Launch, holder: &mut BuyerState, tokens| {
+            let refund = launch.refund_basis(holder, tokens);
+            launch.sol_reserves -= refund;
+            launch.refunded_lamports += refund;
+            refund
+        };
+
+        // The buyer holds only 4 of their 10 tracked tokens at first
+        assert_eq!(claim(&mut launch, &mut holder, 4), 120);
+        assert_eq!((holder.cost_basis, holder.basis_tokens), (360, 6));
+        // The other 6 are refunded at the same ratio, leaving nothing to claim
+        assert_eq!(claim(&mut launch, &mut holder, 6), 180);
+        assert_eq!((holder.cost_basis, holder.basis_tokens), (0, 0));
+        assert_eq!(claim(&mut launch, &mut holder, 1), 0);
+        assert_eq!(launch.refunded_lamports, 300);
+    }
+
+    // xorshift64, so a failing interleaving replays from its seed
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn interleaved_trades_keep_reserves_covering_the_curve() {
+        anchor_lang::solana_program::program_stubs::set_syscall_stubs(Box::new(RuntimeStubs));
+        let config = Config { trade_fee_bps: 100, sell_fee_bps: Some(150), ..Default::default() };
+        let stats = LaunchStats::default();
+        let start = 1_000_000 * UNIT;
+        let launches = [linear_launch(), staged_launch(start), virtual_launch()];
+        for (curve, launch) in launches.into_iter().enumerate() {
+            let mut launch = Launch {
+                state: LaunchState::Trading,
+                initial_supply: start,
+                total_supply: start,
+                ..launch
+            };
+            let mut rng = 0x9e37_79b9_7f4a_7c15 ^ curve as u64;
+            let mut fees = 0;
+            for step in 0..2_000 {
+                let roll = next_random(&mut rng);
+                // Mostly dust and single tokens, with the occasional large trade
+                let amount = match roll % 4 {
+                    0 => 1 + roll % 1_000,
+                    1 => 1 + roll % UNIT,
+                    2 => 1 + roll % (20 * UNIT),
+                    _ => 1 + roll % (5_000 * UNIT),
+                };
+                let sold = launch.total_supply - start;
+                if roll & (1 << 40) != 0 && sold > 0 {
+                    let amount = amount.min(sold);
+                    let (new_supply, proceeds, fee) =
+                        quote_checked_sell(&mut launch, &stats, &config, amount, 0, 0, 0).unwrap();
+                    launch.record_sell(new_supply, proceeds);
+                    fees += fee;
+                } else {
+                    let (new_supply, price) = launch.quote_buy(amount, 0).unwrap();
+                    fees += fee_of(price, config.trade_fee_bps(&launch, true, &stats, 0).unwrap())
+                        .unwrap();
+                    launch.record_buy(new_supply, price).unwrap();
+                }
+
+                let sold = launch.total_supply - start;
+                let owed = launch.curve_cost(start, sold, Rounding::Down).unwrap();
+                assert!(
+                    launch.sol_reserves >= owed,
+                    "curve {curve} step {step}: reserves {} below {owed}",
+                    launch.sol_reserves
+                );
+            }
+            assert!(fees > 0, "curve {curve} charged no fees");
+        }
+    }
+
+    #[test]
+    fn initialize_launch_takes_a_pre_existing_creator_ata() {
+        anchor_lang::solana_program::program_stubs::set_syscall_stubs(Box::new(RuntimeStubs));
+        // init_if_needed validates the existing ATA instead of creating it, which would fail
+        take_invoked();
+        let accounts = initialize_launch_accounts();
+        let ata = *accounts[6].key;
+        let ctx = validate_context::<InitializeLaunch>(accounts, &LAUNCH_PROGRAMS, None).unwrap();
+        assert_eq!(ctx.creator_token_account.key(), ata);
+        assert_eq!(ctx.creator_token_account.owner, ctx.creator.key());
+        assert_eq!(ctx.mint.mint_authority, COption::Some(ctx.mint_authority.key()));
+
+        // The launch, mint and stats are created, but nothing calls the ATA program
+        let invoked = take_invoked();
+        let created = invoked.iter().filter(|ix| ix.program_id == System::id()).count();
+        assert_eq!(created, 3);
+        assert!(invoked.iter().all(|ix| ix.program_id != associated_token::ID));
+    }
+}
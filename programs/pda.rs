@@ -0,0 +1,145 @@
+// programs/meme_launcher/src/pda.rs
+
+// PDA seeds and address derivation. The account constraints and signer seeds in the program
+// use these constants, and off-chain Rust clients can call the `find_*` helpers instead of
+// repeating seed strings, so the two cannot drift apart. Launch accounts are client-generated
+// keypairs rather than PDAs, so every per-launch address derives from the launch key.
+
+use anchor_lang::prelude::*;
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const TREASURY_SEED: &[u8] = b"treasury";
+pub const REWARD_SCHEDULE_SEED: &[u8] = b"reward_schedule";
+pub const REWARDS_POOL_SEED: &[u8] = b"rewards_pool";
+pub const BUYBACK_SEED: &[u8] = b"buyback";
+pub const BLACKLIST_SEED: &[u8] = b"blacklist";
+pub const MARKET_MAKER_SEED: &[u8] = b"market_maker";
+pub const HOOK_ALLOWLIST_SEED: &[u8] = b"hook_allowlist";
+pub const SESSION_SEED: &[u8] = b"session";
+pub const SESSION_ESCROW_SEED: &[u8] = b"session_escrow";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const MINT_AUTHORITY_SEED: &[u8] = b"mint_authority";
+pub const STATS_SEED: &[u8] = b"stats";
+pub const BUYER_STATE_SEED: &[u8] = b"buyer_state";
+pub const RAFFLE_SEED: &[u8] = b"raffle";
+pub const TREE_AUTHORITY_SEED: &[u8] = b"tree_authority";
+pub const OTC_OFFER_SEED: &[u8] = b"otc_offer";
+pub const LOYALTY_POOL_SEED: &[u8] = b"loyalty_pool";
+pub const LIQUIDITY_SEED: &[u8] = b"liquidity";
+pub const COMMUNITY_VAULT_SEED: &[u8] = b"community_vault";
+pub const DEED_SEED: &[u8] = b"deed";
+pub const EARLY_SUPPORTER_SEED: &[u8] = b"early_supporter";
+pub const AIRDROP_CLAIM_SEED: &[u8] = b"airdrop_claim";
+pub const TOKENOMICS_SEED: &[u8] = b"tokenomics";
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+pub const RESERVATION_SEED: &[u8] = b"reservation";
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const VOTE_SEED: &[u8] = b"vote";
+pub const COMPETITION_SEED: &[u8] = b"competition";
+pub const COMPETITION_VAULT_SEED: &[u8] = b"competition_vault";
+pub const COMPETITION_CLAIM_SEED: &[u8] = b"competition_claim";
+pub const LP_REWARD_POOL_SEED: &[u8] = b"lp_reward_pool";
+pub const FEE_SPLIT_SEED: &[u8] = b"fee_split";
+pub const RESIDUAL_VESTING_SEED: &[u8] = b"residual_vesting";
+pub const MILESTONE_POOL_SEED: &[u8] = b"milestone_pool";
+
+pub fn find_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &crate::ID)
+}
+
+pub fn find_treasury_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED], &crate::ID)
+}
+
+pub fn find_buyback_pool_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUYBACK_SEED], &crate::ID)
+}
+
+pub fn find_blacklist_address(wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BLACKLIST_SEED, wallet.as_ref()], &crate::ID)
+}
+
+pub fn find_session_address(owner: &Pubkey, session_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SESSION_SEED, owner.as_ref(), session_key.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn find_session_escrow_address(session: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SESSION_ESCROW_SEED, session.as_ref()], &crate::ID)
+}
+
+pub fn find_vault_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_mint_authority_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_AUTHORITY_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_stats_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATS_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_buyer_state_address(launch: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BUYER_STATE_SEED, launch.as_ref(), wallet.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn find_raffle_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RAFFLE_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_otc_offer_address(launch: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[OTC_OFFER_SEED, launch.as_ref(), seller.as_ref()],
+        &crate::ID,
+    )
+}
+
+pub fn find_liquidity_authority_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LIQUIDITY_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_community_vault_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMMUNITY_VAULT_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_tokenomics_revision_address(launch: &Pubkey, version: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TOKENOMICS_SEED, launch.as_ref(), &version.to_le_bytes()],
+        &crate::ID,
+    )
+}
+
+pub fn find_trade_receipt_address(launch: &Pubkey, seq: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_SEED, launch.as_ref(), &seq.to_le_bytes()], &crate::ID)
+}
+
+pub fn find_competition_address(epoch: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMPETITION_SEED, &epoch.to_le_bytes()], &crate::ID)
+}
+
+pub fn find_competition_vault_address(competition: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMPETITION_VAULT_SEED, competition.as_ref()], &crate::ID)
+}
+
+pub fn find_lp_reward_pool_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LP_REWARD_POOL_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_fee_split_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_SPLIT_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_residual_vesting_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RESIDUAL_VESTING_SEED, launch.as_ref()], &crate::ID)
+}
+
+pub fn find_milestone_pool_address(launch: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MILESTONE_POOL_SEED, launch.as_ref()], &crate::ID)
+}